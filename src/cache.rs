@@ -0,0 +1,104 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// Default lifetime of a cached module value, in seconds.
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    /// Values from another release may lack fields added since; treat them as stale.
+    version: String,
+    created: u64,
+    stamp: Option<u64>,
+    value: T,
+}
+
+/// On-disk cache for slow, rarely changing module values, stored as one JSON
+/// file per module under `<cache dir>/xfetch`.
+#[derive(Debug)]
+pub struct Cache {
+    dir: Option<PathBuf>,
+    ttl: Duration,
+    read: bool,
+}
+
+impl Cache {
+    /// `no_cache` bypasses the cache entirely, `refresh` ignores stored values
+    /// but still writes fresh ones.
+    pub fn new(config: &Config, no_cache: bool, refresh: bool) -> Self {
+        let ttl = config.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL_SECS);
+        let dir = if no_cache || ttl == 0 {
+            None
+        } else {
+            dirs::cache_dir().map(|d| d.join("xfetch"))
+        };
+        Self { dir, ttl: Duration::from_secs(ttl), read: !refresh }
+    }
+
+    /// Returns the value stored under `key` if it is younger than the TTL and
+    /// was written with the same `stamp` (e.g. a package database mtime).
+    pub fn get<T: DeserializeOwned>(&self, key: &str, stamp: Option<u64>) -> Option<T> {
+        if !self.read {
+            return None;
+        }
+        let content = fs::read_to_string(self.path(key)?).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&content).ok()?;
+        let age = now_secs().saturating_sub(entry.created);
+        if age > self.ttl.as_secs() || entry.stamp != stamp || entry.version != env!("CARGO_PKG_VERSION") {
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    pub fn put<T: Serialize>(&self, key: &str, stamp: Option<u64>, value: &T) {
+        let Some(path) = self.path(key) else { return };
+        let entry = Entry { version: env!("CARGO_PKG_VERSION").to_string(), created: now_secs(), stamp, value };
+        if let Some(parent) = path.parent()
+            && fs::create_dir_all(parent).is_ok()
+            && let Ok(json) = serde_json::to_string(&entry)
+        {
+            // A cache that cannot be written is just a slower run, never an error.
+            let _ = fs::write(path, json);
+        }
+    }
+
+    /// Looks `key` up, falling back to `probe` and storing its result. `None`
+    /// results are not cached so that a failed or timed out probe is retried.
+    pub fn fetch<T, F>(&self, key: &str, stamp: Option<u64>, probe: F) -> Option<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Option<T>,
+    {
+        if let Some(value) = self.get(key, stamp) {
+            return Some(value);
+        }
+        let value = probe()?;
+        self.put(key, stamp, &value);
+        Some(value)
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|d| d.join(format!("{}.json", key)))
+    }
+}
+
+/// Latest modification time (seconds since the epoch) among `paths` that exist.
+/// A symlink counts with its own mtime as well as its target's.
+pub fn mtime_stamp<P: AsRef<Path>>(paths: &[P]) -> Option<u64> {
+    paths
+        .iter()
+        .flat_map(|p| [fs::metadata(p), fs::symlink_metadata(p)])
+        .filter_map(|m| m.and_then(|m| m.modified()).ok())
+        .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .max()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,475 @@
+//! Color parsing and SGR generation, with downgrading for terminals that
+//! cannot show 24-bit color.
+
+use clap::ValueEnum;
+use std::env;
+use std::fmt::Display;
+use std::io::IsTerminal;
+
+use crate::terminfo;
+
+/// How many colors the terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Best guess from the environment: `COLORTERM`, terminals known to
+    /// support 24-bit color, then the terminfo entry for `TERM`.
+    pub fn detect() -> Self {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+        let colorterm = var("COLORTERM").to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+
+        let term = var("TERM");
+        // The Linux console and friends stop at 16 colors however they are configured.
+        if (term.is_empty() && cfg!(not(windows))) || term == "dumb" || term == "linux" || term.starts_with("vt") {
+            return ColorDepth::Ansi16;
+        }
+        if term.ends_with("-direct") || TRUECOLOR_TERMS.iter().any(|t| term.starts_with(t)) {
+            return ColorDepth::TrueColor;
+        }
+        if TRUECOLOR_PROGRAMS.contains(&var("TERM_PROGRAM").as_str()) || env::var_os("WT_SESSION").is_some() {
+            return ColorDepth::TrueColor;
+        }
+
+        if let Some(caps) = terminfo::colors(&term) {
+            if caps.truecolor || caps.max_colors.is_some_and(|n| n >= 1 << 24) {
+                return ColorDepth::TrueColor;
+            }
+            if let Some(n) = caps.max_colors {
+                return if n >= 256 { ColorDepth::Ansi256 } else { ColorDepth::Ansi16 };
+            }
+        }
+        if term.contains("256color") {
+            return ColorDepth::Ansi256;
+        }
+        ColorDepth::Ansi16
+    }
+}
+
+/// `TERM` prefixes of terminals that always support 24-bit color.
+const TRUECOLOR_TERMS: &[&str] = &["xterm-kitty", "xterm-ghostty", "alacritty", "wezterm", "foot", "contour"];
+
+/// `TERM_PROGRAM` values of terminals that support 24-bit color whatever `TERM` says.
+const TRUECOLOR_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// One of the 16 terminal palette colors (0-7 normal, 8-15 bright).
+    Named(u8),
+    /// An entry of the 256 color palette.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// xterm's default values for the 16 palette colors, used to blend and to downgrade.
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// Accepts the color names used throughout the config (`red`, `bright-red`,
+    /// `darkred`, `grey`), `#rrggbb`, `rgb(r, g, b)` and 256 color indices (`208`).
+    pub fn parse(value: &str) -> Option<Color> {
+        let value = value.trim().to_lowercase();
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        if let Some(args) = value.strip_prefix("rgb(").and_then(|v| v.strip_suffix(')')) {
+            let channels: Vec<u8> = args.split(',').map(|c| c.trim().parse().ok()).collect::<Option<_>>()?;
+            let [r, g, b] = channels[..] else { return None };
+            return Some(Color::Rgb(r, g, b));
+        }
+        if let Ok(index) = value.parse::<u8>() {
+            return Some(Color::Indexed(index));
+        }
+        // `DarkRed` and friends are the normal palette colors the plain names already select.
+        let value = match value.strip_prefix("dark") {
+            Some(rest) => rest.trim_start_matches(['-', '_']).to_string(),
+            None => value,
+        };
+        if value == "grey" || value == "gray" {
+            return Some(Color::Named(8));
+        }
+        let (name, bright) = match value.strip_prefix("bright-").or_else(|| value.strip_prefix("bright_")) {
+            Some(name) => (name, 8),
+            None => (value.as_str(), 0),
+        };
+        let index = NAMES.iter().position(|n| *n == name)?;
+        Some(Color::Named(index as u8 + bright))
+    }
+
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Named(n) => PALETTE[n as usize % 16],
+            Color::Indexed(n) => indexed_rgb(n),
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// SGR parameters selecting this color as the foreground at `depth`.
+    pub fn fg(self, depth: ColorDepth) -> String {
+        self.sgr(depth, 30)
+    }
+
+    /// SGR parameters selecting this color as the background at `depth`.
+    pub fn bg(self, depth: ColorDepth) -> String {
+        self.sgr(depth, 40)
+    }
+
+    /// `base` is 30 for the foreground and 40 for the background.
+    fn sgr(self, depth: ColorDepth, base: u8) -> String {
+        let extended = base + 8;
+        match (self, depth) {
+            (Color::Named(n), _) | (Color::Indexed(n @ 0..=15), _) => named(n, base),
+            (Color::Indexed(n), ColorDepth::TrueColor | ColorDepth::Ansi256) => format!("{};5;{}", extended, n),
+            (Color::Indexed(n), ColorDepth::Ansi16) => {
+                let (r, g, b) = indexed_rgb(n);
+                named(to_ansi16(r, g, b), base)
+            }
+            (Color::Rgb(r, g, b), ColorDepth::TrueColor) => format!("{};2;{};{};{}", extended, r, g, b),
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => format!("{};5;{}", extended, to_ansi256(r, g, b)),
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => named(to_ansi16(r, g, b), base),
+        }
+    }
+}
+
+/// Normal colors are `base + n`, bright ones live 60 codes higher (90-97, 100-107).
+fn named(n: u8, base: u8) -> String {
+    if n < 8 { (base + n).to_string() } else { (base + 60 + n - 8).to_string() }
+}
+
+/// Channel values of the 6x6x6 color cube in the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Standard xterm values of the 256 color palette.
+fn indexed_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => PALETTE[n as usize],
+        16..=231 => {
+            let i = n - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let grey = 8 + (n - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Nearest entry of the 6x6x6 cube or the grey ramp of the 256 color palette.
+fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| CUBE_LEVELS.iter().enumerate().min_by_key(|(_, l)| (**l as i32 - c as i32).abs()).unwrap().0;
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_index = ((avg.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let grey = 8 + grey_index * 10;
+
+    if distance((grey, grey, grey), (r, g, b)) < distance(cube, (r, g, b)) {
+        232 + grey_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+fn to_ansi16(r: u8, g: u8, b: u8) -> u8 {
+    (0..16u8).min_by_key(|n| distance(PALETTE[*n as usize], (r, g, b))).unwrap_or(7)
+}
+
+/// A foreground color plus text attributes, written in the config as
+/// space separated words, e.g. `"bold #ff8800"` or `"underline bright-cyan"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Style {
+        Style { fg: Some(color), ..Style::default() }
+    }
+
+    pub fn bg(color: Color) -> Style {
+        Style { bg: Some(color), ..Style::default() }
+    }
+
+    /// Unknown words are ignored so that a typo keeps the rest of the style.
+    pub fn parse(value: &str) -> Style {
+        let mut style = Style::default();
+        // `rgb(r, g, b)` may contain spaces, so split on whitespace outside parentheses only.
+        let mut words = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, ch) in value.char_indices() {
+            match ch {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                c if c.is_whitespace() && depth == 0 => {
+                    words.push(&value[start..i]);
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        words.push(&value[start..]);
+
+        for word in words.into_iter().filter(|w| !w.is_empty()) {
+            match word.to_lowercase().as_str() {
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                _ => style.fg = Color::parse(word).or(style.fg),
+            }
+        }
+        style
+    }
+
+    /// SGR parameters for this style, e.g. `1;38;5;208`.
+    pub fn sgr(&self, depth: ColorDepth) -> String {
+        let mut params = Vec::new();
+        for (on, code) in [(self.bold, "1"), (self.dim, "2"), (self.italic, "3"), (self.underline, "4")] {
+            if on {
+                params.push(code.to_string());
+            }
+        }
+        params.extend(self.fg.map(|c| c.fg(depth)));
+        params.extend(self.bg.map(|c| c.bg(depth)));
+        params.join(";")
+    }
+}
+
+/// When to emit colors at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Only when stdout is a terminal, honoring `NO_COLOR` and `CLICOLOR_FORCE`
+    Auto,
+    Always,
+    Never,
+}
+
+/// The single place output gets styled: every renderer goes through it, so
+/// that with colors off the output is plain text without any escape codes.
+#[derive(Debug, Clone, Copy)]
+pub struct Painter {
+    enabled: bool,
+    depth: ColorDepth,
+}
+
+impl Painter {
+    pub fn new(mode: ColorMode) -> Self {
+        let enabled = match mode {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => colors_wanted(),
+        };
+        Self { enabled, depth: ColorDepth::detect() }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Wraps `text` in `style`, resetting afterwards.
+    pub fn paint(&self, style: Style, text: impl Display) -> String {
+        let sgr = style.sgr(self.depth);
+        if !self.enabled || sgr.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", sgr, text)
+    }
+
+    /// Shorthand for painting `text` in a plain foreground color.
+    pub fn color(&self, color: Color, text: impl Display) -> String {
+        self.paint(Style::fg(color), text)
+    }
+
+    /// Opens `style` without closing it, for text colored piece by piece.
+    pub fn start(&self, style: Style) -> String {
+        let sgr = style.sgr(self.depth);
+        if !self.enabled || sgr.is_empty() {
+            return String::new();
+        }
+        format!("\x1b[{}m", sgr)
+    }
+
+    pub fn reset(&self) -> &'static str {
+        if self.enabled { "\x1b[0m" } else { "" }
+    }
+
+    /// Paints each character in its own style, emitting an escape only where it
+    /// changes; `None` keeps the current one (e.g. across whitespace).
+    pub fn paint_runs(&self, cells: impl IntoIterator<Item = (char, Option<Style>)>) -> String {
+        let mut out = String::new();
+        let mut current = String::new();
+        for (ch, style) in cells {
+            // Compare the escapes, not the styles: neighbours often quantize to the same one.
+            if let Some(style) = style
+                && let escape = self.start(style)
+                && escape != current
+            {
+                if !current.is_empty() {
+                    out.push_str(self.reset());
+                }
+                out.push_str(&escape);
+                current = escape;
+            }
+            out.push(ch);
+        }
+        if !current.is_empty() {
+            out.push_str(self.reset());
+        }
+        out
+    }
+}
+
+/// `CLICOLOR_FORCE` wins over `NO_COLOR`, which wins over TTY detection
+/// (see no-color.org and bixense.com/clicolors).
+fn colors_wanted() -> bool {
+    let set = |name: &str| env::var_os(name).is_some_and(|v| !v.is_empty() && v != "0");
+    if set("CLICOLOR_FORCE") {
+        return true;
+    }
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    std::io::stdout().is_terminal()
+}
+
+/// Linear blend through `stops` at `t` in `0.0..=1.0`.
+pub fn gradient(stops: &[Color], t: f64) -> Color {
+    match stops {
+        [] => Color::Named(7),
+        [only] => *only,
+        _ => {
+            let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+            let i = (scaled.floor() as usize).min(stops.len() - 2);
+            let local = scaled - i as f64;
+            let (a, b) = (stops[i].rgb(), stops[i + 1].rgb());
+            let mix = |x: u8, y: u8| (x as f64 + (y as f64 - x as f64) * local).round() as u8;
+            Color::Rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+        }
+    }
+}
+
+/// Fully saturated hue at `t` around the color wheel.
+pub fn rainbow(t: f64) -> Color {
+    let h = t.rem_euclid(1.0) * 6.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let c = |v: f64| (v * 255.0).round() as u8;
+    Color::Rgb(c(r), c(g), c(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_and_rgb() {
+        assert_eq!(Color::parse("#FF8800"), Some(Color::Rgb(255, 136, 0)));
+        assert_eq!(Color::parse(" #0a0b0c "), Some(Color::Rgb(10, 11, 12)));
+        assert_eq!(Color::parse("#fff"), None);
+        assert_eq!(Color::parse("#gg0000"), None);
+        assert_eq!(Color::parse("rgb(1,2,3)"), Some(Color::Rgb(1, 2, 3)));
+        assert_eq!(Color::parse("RGB( 1 , 2 , 3 )"), Some(Color::Rgb(1, 2, 3)));
+        assert_eq!(Color::parse("rgb(1, 2)"), None);
+        assert_eq!(Color::parse("rgb(1, 2, 300)"), None);
+    }
+
+    #[test]
+    fn parses_names_aliases_and_indices() {
+        assert_eq!(Color::parse("red"), Some(Color::Named(1)));
+        assert_eq!(Color::parse("Bright-Cyan"), Some(Color::Named(14)));
+        assert_eq!(Color::parse("bright_white"), Some(Color::Named(15)));
+        assert_eq!(Color::parse("DarkRed"), Some(Color::Named(1)));
+        assert_eq!(Color::parse("dark-blue"), Some(Color::Named(4)));
+        assert_eq!(Color::parse("darkgrey"), Some(Color::Named(8)));
+        assert_eq!(Color::parse("gray"), Some(Color::Named(8)));
+        assert_eq!(Color::parse("0"), Some(Color::Indexed(0)));
+        assert_eq!(Color::parse("208"), Some(Color::Indexed(208)));
+        assert_eq!(Color::parse("256"), None);
+        assert_eq!(Color::parse("bright-grey"), None);
+        assert_eq!(Color::parse("orange"), None);
+    }
+
+    #[test]
+    fn parses_styles_with_spaces_inside_rgb() {
+        let style = Style::parse("bold underline rgb(255, 128, 0)");
+        assert_eq!(style, Style { bold: true, underline: true, ..Style::fg(Color::Rgb(255, 128, 0)) });
+        assert_eq!(style.sgr(ColorDepth::TrueColor), "1;4;38;2;255;128;0");
+        assert_eq!(Style::parse("dim  italic bright-cyan").sgr(ColorDepth::Ansi16), "2;3;96");
+        // Unknown words are skipped, the last color wins.
+        assert_eq!(Style::parse("bold blink green red").sgr(ColorDepth::TrueColor), "1;31");
+        assert_eq!(Style::parse("").sgr(ColorDepth::TrueColor), "");
+    }
+
+    #[test]
+    fn downgrades_colors_to_the_terminal_depth() {
+        assert_eq!(Color::Indexed(208).fg(ColorDepth::Ansi256), "38;5;208");
+        assert_eq!(Color::Indexed(208).bg(ColorDepth::Ansi16), "43");
+        assert_eq!(Color::Indexed(9).fg(ColorDepth::TrueColor), "91");
+        assert_eq!(Color::Rgb(255, 135, 0).fg(ColorDepth::Ansi256), "38;5;208");
+        assert_eq!(Color::Rgb(255, 0, 0).fg(ColorDepth::Ansi16), "91");
+        assert_eq!(Style::bg(Color::Named(4)).sgr(ColorDepth::Ansi256), "44");
+    }
+
+    #[test]
+    fn maps_rgb_to_the_nearest_256_color_entry() {
+        assert_eq!(to_ansi256(0, 0, 0), 16);
+        assert_eq!(to_ansi256(255, 255, 255), 231);
+        assert_eq!(to_ansi256(255, 135, 0), 208);
+        assert_eq!(to_ansi256(250, 140, 10), 208);
+        assert_eq!(to_ansi256(128, 128, 128), 244);
+        assert_eq!(to_ansi256(8, 8, 8), 232);
+        for n in 16..=255 {
+            let (r, g, b) = indexed_rgb(n);
+            assert_eq!(indexed_rgb(to_ansi256(r, g, b)), (r, g, b), "index {}", n);
+        }
+    }
+}
@@ -16,11 +16,46 @@ pub enum ModuleConfig {
     }
 }
 
+impl ModuleConfig {
+    /// Appends every module key in this (possibly nested) entry to `keys`.
+    fn collect_keys<'a>(&'a self, keys: &mut Vec<&'a str>) {
+        match self {
+            ModuleConfig::Simple(key) => keys.push(key),
+            ModuleConfig::Group { modules, .. } => {
+                for module in modules {
+                    module.collect_keys(keys);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Config {
     pub ascii: Option<String>,
     pub logo_path: Option<String>,
+    // Built-in logo name (see --list-logos); detected from os-release when unset
+    pub logo: Option<String>,
+    // Colors for ${c1}..${c9} placeholders in logo files, in order
+    pub logo_colors: Option<Vec<String>>,
+    // flat, gradient, gradient-vertical, lines or rainbow; repaints the whole logo
+    pub logo_style: Option<String>,
+    // Columns xfetch may use; defaults to the terminal width
+    pub max_width: Option<usize>,
+    // Cells a Nerd Font icon takes (1 or 2), depending on font and terminal
+    pub nerd_font_width: Option<usize>,
+    // left (default), right, top, bottom or none
+    pub logo_position: Option<String>,
+    // Size of image logos in terminal cells
+    pub logo_width: Option<u32>,
+    pub logo_height: Option<u32>,
+    // auto (default), kitty, iterm, sixel or half-blocks
+    pub logo_protocol: Option<String>,
+    // Below these terminal widths the logo is shrunk, moved above the info, or dropped
+    pub logo_compact_below: Option<usize>,
+    pub logo_stack_below: Option<usize>,
+    pub logo_hide_below: Option<usize>,
     pub modules: Vec<ModuleConfig>, // Changed from Vec<String>
     pub show_colors: bool,
     pub icons: HashMap<String, String>,
@@ -29,6 +64,9 @@ pub struct Config {
     pub header_icons: Option<Vec<String>>,
     pub footer_text: Option<String>,
     pub palette_style: Option<String>, // "dots", "squares", "lines", "triangles"
+    pub command_timeout_ms: Option<u64>, // Per external command (lspci, date, ...)
+    pub cache_ttl: Option<u64>, // Seconds; 0 disables the module cache
+    pub package_managers: Option<Vec<String>>, // Managers counted by "packages"; all when unset
 }
 
 impl Default for Config {
@@ -58,6 +96,18 @@ impl Default for Config {
         Self {
             ascii: None,
             logo_path: None,
+            logo: None,
+            logo_colors: None,
+            logo_style: None,
+            max_width: None,
+            nerd_font_width: None,
+            logo_position: None,
+            logo_width: None,
+            logo_height: None,
+            logo_protocol: None,
+            logo_compact_below: None,
+            logo_stack_below: None,
+            logo_hide_below: None,
             modules: vec![
                 ModuleConfig::Simple("os".to_string()),
                 ModuleConfig::Simple("kernel".to_string()),
@@ -78,7 +128,21 @@ impl Default for Config {
             header_icons: None,
             footer_text: None,
             palette_style: None,
+            command_timeout_ms: None,
+            cache_ttl: None,
+            package_managers: None,
+        }
+    }
+}
+
+impl Config {
+    /// Flattened list of module keys referenced by `modules`, in display order.
+    pub fn module_keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        for module in &self.modules {
+            module.collect_keys(&mut keys);
         }
+        keys
     }
 }
 
@@ -109,13 +173,7 @@ pub fn load_config(path: Option<String>) -> Config {
     
     // println!("Debug: Config content: {}", content);
 
-    match serde_json::from_str(&content) {
-        Ok(c) => c,
-        Err(_e) => {
-            // println!("Debug: Failed to parse config: {}", e);
-            Config::default()
-        }
-    }
+    serde_json::from_str(&content).unwrap_or_default()
 }
 
 pub fn default_config_path() -> PathBuf {
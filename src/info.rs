@@ -1,121 +1,478 @@
+use crate::cache::{mtime_stamp, Cache};
+use crate::config::Config;
+use crate::packages;
+use crate::timings::Timings;
+use serde::{Deserialize, Serialize};
 use sysinfo::{
-    Components, Disks, Networks, System,
+    CpuRefreshKind, Disks, MemoryRefreshKind, Networks, RefreshKind, System,
 };
+use std::collections::{HashMap, HashSet};
 use std::env;
-#[cfg(target_os = "linux")]
+use std::net::{IpAddr, Ipv4Addr};
 use std::fs;
-use std::process::Command;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, ScopedJoinHandle};
+use std::time::{Duration, Instant};
 
+/// Checked in order, as described in os-release(5).
+const OS_RELEASE_PATHS: &[&str] = &["/etc/os-release", "/usr/lib/os-release"];
+
+/// How long an external command (lspci, date, ...) may run before its module is reported as unknown.
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Info {
-    pub os: String,
-    pub kernel: String,
-    pub host_name: String,
-    pub shell: String,
-    pub terminal: String,
-    pub cpu: String,
+    pub os: Option<OsInfo>,
+    pub kernel: Option<String>,
+    pub host_name: Option<String>,
+    pub shell: Option<String>,
+    pub terminal: Option<String>,
+    pub cpu: Option<CpuInfo>,
     pub gpu: Vec<String>,
-    pub memory: String,
-    pub swap: String,
-    pub disks: Vec<String>,
-    pub battery: String,
-    pub uptime: String,
-    pub packages: String,
-    pub desktop: String,
-    pub user: String,
-    pub datetime: String,
-    pub local_ip: String,
+    pub memory: Option<MemoryInfo>,
+    pub swap: Option<MemoryInfo>,
+    pub disks: Vec<DiskInfo>,
+    pub battery: Option<BatteryInfo>,
+    pub uptime_secs: Option<u64>,
+    pub packages: Vec<PackageCount>,
+    pub desktop: Option<String>,
+    pub user: Option<String>,
+    pub datetime: Option<String>,
+    pub local_ip: Option<Ipv4Addr>,
+}
+
+/// A single piece of system information that `Info::collect` knows how to gather.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Probe {
+    Os,
+    Kernel,
+    HostName,
+    Shell,
+    Terminal,
+    Cpu,
+    Gpu,
+    Memory,
+    Swap,
+    Disks,
+    Battery,
+    Uptime,
+    Packages,
+    Desktop,
+    User,
+    DateTime,
+    LocalIp,
+}
+
+impl Probe {
+    pub const ALL: [Probe; 17] = [
+        Probe::Os,
+        Probe::Kernel,
+        Probe::HostName,
+        Probe::Shell,
+        Probe::Terminal,
+        Probe::Cpu,
+        Probe::Gpu,
+        Probe::Memory,
+        Probe::Swap,
+        Probe::Disks,
+        Probe::Battery,
+        Probe::Uptime,
+        Probe::Packages,
+        Probe::Desktop,
+        Probe::User,
+        Probe::DateTime,
+        Probe::LocalIp,
+    ];
+
+    /// Stable lowercase identifier, used in timing reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Probe::Os => "os",
+            Probe::Kernel => "kernel",
+            Probe::HostName => "hostname",
+            Probe::Shell => "shell",
+            Probe::Terminal => "terminal",
+            Probe::Cpu => "cpu",
+            Probe::Gpu => "gpu",
+            Probe::Memory => "memory",
+            Probe::Swap => "swap",
+            Probe::Disks => "disks",
+            Probe::Battery => "battery",
+            Probe::Uptime => "uptime",
+            Probe::Packages => "packages",
+            Probe::Desktop => "desktop",
+            Probe::User => "user",
+            Probe::DateTime => "datetime",
+            Probe::LocalIp => "local_ip",
+        }
+    }
+
+    /// Probes needed to render the module `key` (empty for purely decorative modules).
+    pub fn for_module(key: &str) -> &'static [Probe] {
+        match key {
+            "os" => &[Probe::Os],
+            key if key.starts_with("os.") => &[Probe::Os],
+            "kernel" => &[Probe::Kernel],
+            "hostname" | "host" => &[Probe::HostName],
+            "wm" => &[Probe::Desktop],
+            "packages" => &[Probe::Packages],
+            "shell" => &[Probe::Shell],
+            "cpu" => &[Probe::Cpu],
+            "gpu" => &[Probe::Gpu],
+            "memory" => &[Probe::Memory],
+            "swap" => &[Probe::Swap],
+            "disk" => &[Probe::Disks],
+            "battery" => &[Probe::Battery],
+            "uptime" => &[Probe::Uptime],
+            "terminal" => &[Probe::Terminal],
+            "user" => &[Probe::User],
+            "datetime" => &[Probe::DateTime],
+            "local_ip" => &[Probe::LocalIp],
+            "header" => &[Probe::User, Probe::HostName],
+            _ => &[],
+        }
+    }
+
+    /// Probes needed to render every module listed in `config`.
+    pub fn for_config(config: &Config) -> HashSet<Probe> {
+        let mut probes: HashSet<Probe> = config
+            .module_keys()
+            .into_iter()
+            .flat_map(|key| Probe::for_module(key).iter().copied())
+            .collect();
+        // The built-in logo is picked from os-release unless one is configured;
+        // image logos fall back to it when they cannot be shown.
+        if config.ascii.is_none() && config.logo.is_none() {
+            probes.insert(Probe::Os);
+        }
+        probes
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub arch: String,
+    /// The remaining fields come from os-release and are `None` where it is missing (Windows, macOS).
+    pub pretty_name: Option<String>,
+    pub id: Option<String>,
+    #[serde(default)]
+    pub id_like: Vec<String>,
+    pub version_codename: Option<String>,
+    pub build_id: Option<String>,
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuInfo {
+    pub brand: String,
+    pub cores: Option<usize>,
+    pub threads: usize,
+    pub freq_mhz: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryInfo {
+    pub used_bytes: u64,
+    pub total_bytes: u64,
+    pub percent: f64,
+}
+
+impl MemoryInfo {
+    fn new(used_bytes: u64, total_bytes: u64) -> Self {
+        Self { used_bytes, total_bytes, percent: percent_of(used_bytes, total_bytes) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskInfo {
+    pub mount: String,
+    pub fs: String,
+    pub used_bytes: u64,
+    pub total_bytes: u64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatteryInfo {
+    pub percent: u8,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageCount {
+    pub manager: String,
+    /// Installation the count belongs to when a manager has several (e.g. "user" or "system").
+    pub source: Option<String>,
+    pub count: usize,
+}
+
+fn percent_of(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    used as f64 / total as f64 * 100.0
 }
 
 impl Info {
-    pub fn new() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
-        let disks = Disks::new_with_refreshed_list();
-        let networks = Networks::new_with_refreshed_list();
-        let components = Components::new_with_refreshed_list();
-
-        Self {
-            os: get_os_info(),
-            kernel: get_kernel_info(),
-            host_name: get_host_name(),
-            shell: get_shell_info(),
-            terminal: get_terminal_info(),
-            cpu: get_cpu_info(&sys),
-            gpu: get_gpu_info(),
-            memory: get_memory_info(&sys),
-            swap: get_swap_info(&sys),
-            disks: get_disk_info(&disks),
-            battery: get_battery_info(&components),
-            uptime: get_uptime_info(),
-            packages: get_packages_info(),
-            desktop: get_desktop_info(),
-            user: get_user_info(),
-            datetime: get_datetime_info(),
-            local_ip: get_local_ip_info(&networks),
+    /// Gathers only the requested probes, refreshing just the sysinfo subsystems they need.
+    /// Probes that touch the filesystem or spawn external commands run concurrently, and
+    /// values that rarely change (OS, CPU, GPU, packages) are served from `cache` when fresh.
+    pub fn collect(probes: &HashSet<Probe>, config: &Config, cache: &Cache, timings: &Timings) -> Self {
+        let wants = |probe| probes.contains(&probe);
+        let timeout = Duration::from_millis(config.command_timeout_ms.unwrap_or(DEFAULT_COMMAND_TIMEOUT_MS));
+
+        let mut info = Self::default();
+        if wants(Probe::Cpu) {
+            info.cpu = cache.get("cpu", None);
+        }
+        let probe_cpu = wants(Probe::Cpu) && info.cpu.is_none();
+
+        thread::scope(|s| {
+            let sys = (probe_cpu || wants(Probe::Memory) || wants(Probe::Swap)).then(|| {
+                s.spawn(|| {
+                    timings.time("probe.sysinfo", || {
+                        let mut refresh = RefreshKind::nothing();
+                        if probe_cpu {
+                            refresh = refresh.with_cpu(CpuRefreshKind::nothing().with_frequency());
+                        }
+                        if wants(Probe::Memory) || wants(Probe::Swap) {
+                            refresh = refresh.with_memory(MemoryRefreshKind::nothing().with_ram().with_swap());
+                        }
+                        System::new_with_specifics(refresh)
+                    })
+                })
+            });
+            let gpu = wants(Probe::Gpu).then(|| {
+                s.spawn(|| timed(timings, Probe::Gpu, || cache.fetch("gpu", None, || non_empty(get_gpu_info(timeout)))))
+            });
+            let disks = wants(Probe::Disks)
+                .then(|| s.spawn(|| timed(timings, Probe::Disks, || get_disk_info(&Disks::new_with_refreshed_list()))));
+            let packages = wants(Probe::Packages).then(|| {
+                s.spawn(|| {
+                    timed(timings, Probe::Packages, || {
+                        let managers = config.package_managers.as_deref();
+                        // Keyed by the managers that matched, so odd config entries cannot escape the cache dir.
+                        let key = match managers {
+                            Some(_) => format!("packages-{}", packages::selected(managers).join("-")),
+                            None => "packages".to_string(),
+                        };
+                        let stamp = mtime_stamp(&packages::db_paths());
+                        cache.fetch(&key, stamp, || non_empty(packages::get_packages_info(managers, timeout)))
+                    })
+                })
+            });
+            let datetime = wants(Probe::DateTime)
+                .then(|| s.spawn(|| timed(timings, Probe::DateTime, || get_datetime_info(timeout))));
+            let local_ip = wants(Probe::LocalIp)
+                .then(|| s.spawn(|| timed(timings, Probe::LocalIp, || get_local_ip_info(&Networks::new_with_refreshed_list()))));
+
+            // Cheap probes (environment variables and small files) run inline.
+            if wants(Probe::Os) {
+                info.os = timed(timings, Probe::Os, || cache.fetch("os", mtime_stamp(OS_RELEASE_PATHS), || Some(get_os_info())));
+            }
+            if wants(Probe::Kernel) {
+                info.kernel = timed(timings, Probe::Kernel, get_kernel_info);
+            }
+            if wants(Probe::HostName) {
+                info.host_name = timed(timings, Probe::HostName, get_host_name);
+            }
+            if wants(Probe::Shell) {
+                info.shell = timed(timings, Probe::Shell, get_shell_info);
+            }
+            if wants(Probe::Terminal) {
+                info.terminal = timed(timings, Probe::Terminal, get_terminal_info);
+            }
+            if wants(Probe::Battery) {
+                info.battery = timed(timings, Probe::Battery, get_battery_info);
+            }
+            if wants(Probe::Uptime) {
+                info.uptime_secs = Some(timed(timings, Probe::Uptime, System::uptime));
+            }
+            if wants(Probe::Desktop) {
+                info.desktop = timed(timings, Probe::Desktop, get_desktop_info);
+            }
+            if wants(Probe::User) {
+                info.user = timed(timings, Probe::User, get_user_info);
+            }
+
+            if let Some(sys) = sys.and_then(|h| h.join().ok()) {
+                if probe_cpu {
+                    info.cpu = timed(timings, Probe::Cpu, || get_cpu_info(&sys));
+                    if let Some(cpu) = &info.cpu {
+                        cache.put("cpu", None, cpu);
+                    }
+                }
+                if wants(Probe::Memory) {
+                    info.memory = Some(timed(timings, Probe::Memory, || get_memory_info(&sys)));
+                }
+                if wants(Probe::Swap) {
+                    info.swap = Some(timed(timings, Probe::Swap, || get_swap_info(&sys)));
+                }
+            }
+            info.gpu = join_probe(gpu).flatten().unwrap_or_default();
+            info.disks = join_probe(disks).unwrap_or_default();
+            info.packages = join_probe(packages).flatten().unwrap_or_default();
+            info.datetime = join_probe(datetime).flatten();
+            info.local_ip = join_probe(local_ip).flatten();
+        });
+        info
+    }
+}
+
+fn timed<T>(timings: &Timings, probe: Probe, f: impl FnOnce() -> T) -> T {
+    timings.time(&format!("probe.{}", probe.name()), f)
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    (!values.is_empty()).then_some(values)
+}
+
+/// Waits for a probe spawned in `Info::collect`, treating a panicked probe as missing.
+fn join_probe<T>(handle: Option<ScopedJoinHandle<'_, T>>) -> Option<T> {
+    handle.and_then(|h| h.join().ok())
+}
+
+/// Runs `cmd` like `Command::output`, but kills it and returns `None` if it
+/// has not exited within `timeout`.
+pub fn run_command(cmd: &mut Command, timeout: Duration) -> Option<Output> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Drain stdout on a separate thread so a chatty command cannot block on a full pipe.
+    let mut pipe = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
         }
+    };
+
+    let stdout = reader.join().ok()?;
+    Some(Output { status, stdout, stderr: Vec::new() })
+}
+
+fn get_os_info() -> OsInfo {
+    let release = OS_RELEASE_PATHS
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok())
+        .map(|content| parse_os_release(&content))
+        .unwrap_or_default();
+    let field = |key: &str| release.get(key).filter(|v| !v.is_empty()).cloned();
+
+    OsInfo {
+        name: field("NAME").or_else(System::name),
+        version: field("VERSION_ID").or_else(System::os_version),
+        arch: std::env::consts::ARCH.to_string(),
+        pretty_name: field("PRETTY_NAME"),
+        id: field("ID"),
+        id_like: field("ID_LIKE").map(|v| v.split_whitespace().map(str::to_string).collect()).unwrap_or_default(),
+        version_codename: field("VERSION_CODENAME"),
+        build_id: field("BUILD_ID"),
+        variant: field("VARIANT"),
     }
 }
 
-fn get_os_info() -> String {
-    let name = System::name().unwrap_or("Unknown".to_string());
-    let version = System::os_version().unwrap_or("".to_string());
-    let arch = std::env::consts::ARCH;
-    format!("{} {} {}", name, version, arch)
+/// Parses the `KEY=value` lines of an os-release file, undoing shell-style quoting.
+fn parse_os_release(content: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, raw)) = line.split_once('=') else { continue };
+        let value = match raw.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = raw[1..].strip_suffix(quote).unwrap_or(&raw[1..]);
+                if quote == '"' {
+                    let mut unescaped = String::with_capacity(inner.len());
+                    let mut chars = inner.chars();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => unescaped.extend(chars.next()),
+                            c => unescaped.push(c),
+                        }
+                    }
+                    unescaped
+                } else {
+                    inner.to_string()
+                }
+            }
+            _ => raw.to_string(),
+        };
+        fields.insert(key.trim().to_string(), value);
+    }
+    fields
 }
 
-fn get_kernel_info() -> String {
-    let version = System::kernel_version().unwrap_or("Unknown".to_string());
-    format!("{}", version)
+fn get_kernel_info() -> Option<String> {
+    System::kernel_version()
 }
 
-fn get_host_name() -> String {
-    System::host_name().unwrap_or("Unknown".to_string())
+fn get_host_name() -> Option<String> {
+    System::host_name()
 }
 
-fn get_shell_info() -> String {
+fn get_shell_info() -> Option<String> {
     if let Ok(shell) = env::var("SHELL") {
         let path = std::path::Path::new(&shell);
         if let Some(name) = path.file_name() {
-            return name.to_string_lossy().into_owned();
+            return Some(name.to_string_lossy().into_owned());
         }
     }
     if cfg!(target_os = "windows") {
         if env::var("PSModulePath").is_ok() {
-            return "PowerShell".to_string();
+            return Some("PowerShell".to_string());
         }
-        return "cmd".to_string();
+        return Some("cmd".to_string());
     }
-    "Unknown".to_string()
+    None
 }
 
-fn get_terminal_info() -> String {
+fn get_terminal_info() -> Option<String> {
     if let Ok(term) = env::var("TERM_PROGRAM") {
-        return term;
-    }
-    if let Ok(_) = env::var("WT_SESSION") {
-        return "Windows Terminal".to_string();
+        return Some(term);
     }
-    if let Ok(term) = env::var("TERM") {
-        return term;
+    if env::var("WT_SESSION").is_ok() {
+        return Some("Windows Terminal".to_string());
     }
-    "Unknown".to_string()
+    env::var("TERM").ok()
 }
 
-fn get_cpu_info(sys: &System) -> String {
+fn get_cpu_info(sys: &System) -> Option<CpuInfo> {
     let cpus = sys.cpus();
-    if cpus.is_empty() {
-        return "Unknown".to_string();
-    }
-    let brand = cpus[0].brand();
-    let freq = cpus[0].frequency(); // MHz
-    let cores = cpus.len();
-    format!("{} ({}) @ {:.2} GHz", brand, cores, freq as f64 / 1000.0)
+    let first = cpus.first()?;
+    Some(CpuInfo {
+        brand: first.brand().to_string(),
+        cores: System::physical_core_count(),
+        threads: cpus.len(),
+        freq_mhz: first.frequency(),
+    })
 }
 
-fn get_gpu_info() -> Vec<String> {
+fn get_gpu_info(timeout: Duration) -> Vec<String> {
     let mut gpus = Vec::new();
     if cfg!(target_os = "linux") {
-        if let Ok(output) = Command::new("lspci").arg("-mm").output() {
+        if let Some(output) = run_command(Command::new("lspci").arg("-mm"), timeout) {
             let out = String::from_utf8_lossy(&output.stdout);
             for line in out.lines() {
                 if line.contains("VGA") || line.contains("3D") || line.contains("Display") {
@@ -128,7 +485,7 @@ fn get_gpu_info() -> Vec<String> {
         }
     }
     else if cfg!(target_os = "windows") {
-        if let Ok(output) = Command::new("wmic").args(&["path", "win32_videocontroller", "get", "name"]).output() {
+        if let Some(output) = run_command(Command::new("wmic").args(["path", "win32_videocontroller", "get", "name"]), timeout) {
             let out = String::from_utf8_lossy(&output.stdout);
             for line in out.lines().skip(1) {
                 let trimmed = line.trim();
@@ -138,146 +495,130 @@ fn get_gpu_info() -> Vec<String> {
             }
         }
     }
-    else if cfg!(target_os = "macos") {
-        if let Ok(output) = Command::new("system_profiler").arg("SPDisplaysDataType").output() {
-             let out = String::from_utf8_lossy(&output.stdout);
-             for line in out.lines() {
-                 if line.trim().starts_with("Chipset Model:") {
-                     gpus.push(line.trim().replace("Chipset Model: ", ""));
-                 }
-             }
+    else if cfg!(target_os = "macos")
+        && let Some(output) = run_command(Command::new("system_profiler").arg("SPDisplaysDataType"), timeout)
+    {
+        let out = String::from_utf8_lossy(&output.stdout);
+        for line in out.lines() {
+            if line.trim().starts_with("Chipset Model:") {
+                gpus.push(line.trim().replace("Chipset Model: ", ""));
+            }
         }
     }
-    if gpus.is_empty() {
-        gpus.push("Unknown GPU".to_string());
-    }
     gpus
 }
 
-fn get_memory_info(sys: &System) -> String {
-    let total = sys.total_memory() as f64 / 1024.0 / 1024.0 / 1024.0;
-    let used = sys.used_memory() as f64 / 1024.0 / 1024.0 / 1024.0;
-    let percent = (used / total) * 100.0;
-    format!("{:.2} GiB / {:.2} GiB ({:.0}%)", used, total, percent)
+fn get_memory_info(sys: &System) -> MemoryInfo {
+    MemoryInfo::new(sys.used_memory(), sys.total_memory())
 }
 
-fn get_swap_info(sys: &System) -> String {
-    let total = sys.total_swap() as f64 / 1024.0 / 1024.0 / 1024.0;
-    let used = sys.used_swap() as f64 / 1024.0 / 1024.0 / 1024.0;
-    if total == 0.0 {
-        return "0 B / 0 B (0%)".to_string();
-    }
-    let percent = (used / total) * 100.0;
-    format!("{:.2} GiB / {:.2} GiB ({:.0}%)", used, total, percent)
+fn get_swap_info(sys: &System) -> MemoryInfo {
+    MemoryInfo::new(sys.used_swap(), sys.total_swap())
 }
 
-fn get_disk_info(disks: &Disks) -> Vec<String> {
+fn get_disk_info(disks: &Disks) -> Vec<DiskInfo> {
     let mut disk_list = Vec::new();
     for disk in disks {
-        let total = disk.total_space() as f64 / 1024.0 / 1024.0 / 1024.0;
-        let available = disk.available_space() as f64 / 1024.0 / 1024.0 / 1024.0;
-        let used = total - available;
-        let percent = (used / total) * 100.0;
-        let fs = disk.file_system().to_str().unwrap_or("Unknown");
-        disk_list.push(format!("{:.2} GiB / {:.2} GiB ({:.0}%) - {}", used, total, percent, fs));
+        let total = disk.total_space();
+        let used = total.saturating_sub(disk.available_space());
+        disk_list.push(DiskInfo {
+            mount: disk.mount_point().to_string_lossy().into_owned(),
+            fs: disk.file_system().to_string_lossy().into_owned(),
+            used_bytes: used,
+            total_bytes: total,
+            percent: percent_of(used, total),
+        });
     }
     disk_list
 }
 
-fn get_battery_info(_components: &Components) -> String {
+fn get_battery_info() -> Option<BatteryInfo> {
     #[cfg(target_os = "linux")]
     {
-        if let Ok(cap) = fs::read_to_string("/sys/class/power_supply/BAT0/capacity") {
+        if let Ok(cap) = fs::read_to_string("/sys/class/power_supply/BAT0/capacity")
+            && let Ok(percent) = cap.trim().parse()
+        {
             let status = fs::read_to_string("/sys/class/power_supply/BAT0/status").unwrap_or("Unknown".to_string());
-            return format!("{}% [{}]", cap.trim(), status.trim());
-        }
-    }
-    "100% [AC Connected]".to_string()
-}
-
-fn get_uptime_info() -> String {
-    let uptime = System::uptime();
-    let hours = uptime / 3600;
-    let mins = (uptime % 3600) / 60;
-    format!("{} hours, {} mins", hours, mins)
-}
-
-fn get_packages_info() -> String {
-    if cfg!(target_os = "linux") {
-        if let Ok(output) = Command::new("pacman").arg("-Qq").output() {
-            if output.status.success() {
-                 let count = String::from_utf8_lossy(&output.stdout).lines().count();
-                 return format!("{} (pacman)", count);
-            }
-        }
-        if let Ok(output) = Command::new("dpkg").arg("--get-selections").output() {
-             if output.status.success() {
-                 let count = String::from_utf8_lossy(&output.stdout).lines().count();
-                 return format!("{} (dpkg)", count);
-            }
-        }
-    }
-    if cfg!(target_os = "windows") {
-        if let Ok(output) = Command::new("scoop").arg("list").output() {
-             if output.status.success() {
-                 let count = String::from_utf8_lossy(&output.stdout).lines().count().saturating_sub(4);
-                 return format!("{} (scoop)", count);
-            }
-        }
-    }
-    if cfg!(target_os = "macos") {
-        if let Ok(output) = Command::new("brew").arg("list").arg("--formula").output() {
-             if output.status.success() {
-                 let count = String::from_utf8_lossy(&output.stdout).lines().count();
-                 return format!("{} (brew)", count);
-            }
+            return Some(BatteryInfo { percent, state: status.trim().to_string() });
         }
     }
-    "Unknown".to_string()
+    None
 }
 
-fn get_desktop_info() -> String {
+fn get_desktop_info() -> Option<String> {
     if let Ok(de) = env::var("XDG_CURRENT_DESKTOP") {
-        return de;
+        return Some(de);
     }
     if let Ok(de) = env::var("DESKTOP_SESSION") {
-        return de;
+        return Some(de);
     }
     if cfg!(target_os = "windows") {
-        return "Explorer".to_string();
+        return Some("Explorer".to_string());
     }
     if cfg!(target_os = "macos") {
-        return "Aqua".to_string();
+        return Some("Aqua".to_string());
     }
-    "Unknown".to_string()
+    None
 }
 
-fn get_user_info() -> String {
-    env::var("USER").unwrap_or_else(|_| "Unknown".to_string())
+fn get_user_info() -> Option<String> {
+    env::var("USER").ok()
 }
 
-fn get_datetime_info() -> String {
-    if cfg!(target_os = "windows") {
-        if let Ok(output) = Command::new("powershell").arg("-Command").arg("Get-Date -Format 'yyyy-MM-dd HH:mm:ss'").output() {
-             return String::from_utf8_lossy(&output.stdout).trim().to_string();
-        }
+fn get_datetime_info(timeout: Duration) -> Option<String> {
+    let output = if cfg!(target_os = "windows") {
+        run_command(Command::new("powershell").arg("-Command").arg("Get-Date -Format 'yyyy-MM-dd HH:mm:ss'"), timeout)
     } else {
-        if let Ok(output) = Command::new("date").arg("+%Y-%m-%d %H:%M:%S").output() {
-            return String::from_utf8_lossy(&output.stdout).trim().to_string();
-        }
-    }
-    "Unknown".to_string()
+        run_command(Command::new("date").arg("+%Y-%m-%d %H:%M:%S"), timeout)
+    };
+    output.map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
 
-fn get_local_ip_info(networks: &Networks) -> String {
+fn get_local_ip_info(networks: &Networks) -> Option<Ipv4Addr> {
     for (_name, data) in networks {
         for ip in data.ip_networks() {
-             if let std::net::IpAddr::V4(ipv4) = ip.addr {
-                 if !ipv4.is_loopback() {
-                     return ipv4.to_string();
-                 }
-             }
+            if let IpAddr::V4(ipv4) = ip.addr
+                && !ipv4.is_loopback()
+            {
+                return Some(ipv4);
+            }
         }
     }
-    "127.0.0.1".to_string()
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_quoted_values() {
+        let fields = parse_os_release(
+            "# comment\n\nNAME=\"Fedora Linux\"\nID=fedora\nVERSION_ID='40'\nID_LIKE=\"rhel centos\"\nEMPTY=\n",
+        );
+        assert_eq!(fields["NAME"], "Fedora Linux");
+        assert_eq!(fields["ID"], "fedora");
+        assert_eq!(fields["VERSION_ID"], "40");
+        assert_eq!(fields["ID_LIKE"], "rhel centos");
+        assert_eq!(fields["EMPTY"], "");
+        assert_eq!(fields.len(), 5);
+    }
+
+    #[test]
+    fn unescapes_double_quoted_values_only() {
+        let fields = parse_os_release(
+            "PRETTY_NAME=\"Say \\\"hi\\\" \\$HOME \\\\ \\`x\\`\"\nSINGLE='a\\b'\nBARE=a\\b\n",
+        );
+        assert_eq!(fields["PRETTY_NAME"], "Say \"hi\" $HOME \\ `x`");
+        assert_eq!(fields["SINGLE"], "a\\b");
+        assert_eq!(fields["BARE"], "a\\b");
+    }
+
+    #[test]
+    fn tolerates_unterminated_quotes_and_junk_lines() {
+        let fields = parse_os_release("NAME=\"Arch Linux\nnot a field\n  ID=arch  \n");
+        assert_eq!(fields["NAME"], "Arch Linux");
+        assert_eq!(fields["ID"], "arch");
+        assert_eq!(fields.len(), 2);
+    }
 }
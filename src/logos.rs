@@ -0,0 +1,142 @@
+use crate::info::OsInfo;
+
+/// An ASCII logo compiled into the binary.
+#[derive(Debug)]
+pub struct Logo {
+    pub name: &'static str,
+    /// os-release `ID` values this logo stands for; `ID_LIKE` is consulted when `ID` has no match.
+    pub ids: &'static [&'static str],
+    pub art: &'static str,
+    /// Compact variant for narrow terminals, when the logo is not small already.
+    pub small: Option<&'static str>,
+    /// Color the logo is painted with (RGB).
+    pub color: (u8, u8, u8),
+}
+
+pub const LOGOS: &[Logo] = &[
+    Logo {
+        name: "alpine",
+        ids: &["alpine"],
+        art: include_str!("../logos/distros/alpine.txt"),
+        small: Some(include_str!("../logos/distros/alpine_small.txt")),
+        color: (13, 89, 127),
+    },
+    Logo {
+        name: "arch",
+        ids: &["arch", "archarm", "archlinux"],
+        art: include_str!("../logos/distros/arch.txt"),
+        small: None,
+        color: (23, 147, 209),
+    },
+    Logo {
+        name: "centos",
+        ids: &["centos", "rhel", "rocky", "almalinux"],
+        art: include_str!("../logos/distros/centos.txt"),
+        small: Some(include_str!("../logos/distros/centos_small.txt")),
+        color: (148, 45, 143),
+    },
+    Logo {
+        name: "debian",
+        ids: &["debian", "raspbian"],
+        art: include_str!("../logos/distros/debian.txt"),
+        small: Some(include_str!("../logos/distros/debian_small.txt")),
+        color: (215, 10, 83),
+    },
+    Logo {
+        name: "fedora",
+        ids: &["fedora"],
+        art: include_str!("../logos/distros/fedora.txt"),
+        small: None,
+        color: (81, 162, 218),
+    },
+    Logo {
+        name: "gentoo",
+        ids: &["gentoo"],
+        art: include_str!("../logos/distros/gentoo.txt"),
+        small: Some(include_str!("../logos/distros/gentoo_small.txt")),
+        color: (151, 145, 208),
+    },
+    Logo {
+        name: "linuxmint",
+        ids: &["linuxmint", "mint"],
+        art: include_str!("../logos/distros/linuxmint.txt"),
+        small: Some(include_str!("../logos/distros/linuxmint_small.txt")),
+        color: (135, 207, 62),
+    },
+    Logo {
+        name: "macos",
+        ids: &["macos", "darwin"],
+        art: include_str!("../logos/distros/macos.txt"),
+        small: Some(include_str!("../logos/distros/macos_small.txt")),
+        color: (168, 168, 168),
+    },
+    Logo {
+        name: "manjaro",
+        ids: &["manjaro"],
+        art: include_str!("../logos/distros/manjaro.txt"),
+        small: Some(include_str!("../logos/distros/manjaro_small.txt")),
+        color: (53, 191, 164),
+    },
+    Logo {
+        name: "nixos",
+        ids: &["nixos", "nix"],
+        art: include_str!("../logos/distros/nixos.txt"),
+        small: None,
+        color: (126, 186, 228),
+    },
+    Logo {
+        name: "opensuse",
+        ids: &["opensuse", "opensuse-tumbleweed", "opensuse-leap", "suse", "sles"],
+        art: include_str!("../logos/distros/opensuse.txt"),
+        small: Some(include_str!("../logos/distros/opensuse_small.txt")),
+        color: (115, 186, 37),
+    },
+    Logo {
+        name: "ubuntu",
+        ids: &["ubuntu", "pop", "elementary", "zorin"],
+        art: include_str!("../logos/distros/ubuntu.txt"),
+        small: Some(include_str!("../logos/distros/ubuntu_small.txt")),
+        color: (233, 84, 32),
+    },
+    Logo {
+        name: "void",
+        ids: &["void"],
+        art: include_str!("../logos/distros/void.txt"),
+        small: None,
+        color: (71, 128, 97),
+    },
+    Logo {
+        name: "windows",
+        ids: &["windows"],
+        art: include_str!("../logos/distros/windows.txt"),
+        small: Some(include_str!("../logos/distros/windows_small.txt")),
+        color: (0, 120, 212),
+    },
+];
+
+/// Looks a logo up by its name or by any of the os-release IDs it covers.
+pub fn find(name: &str) -> Option<&'static Logo> {
+    let name = name.trim().to_lowercase();
+    LOGOS
+        .iter()
+        .find(|l| l.name == name)
+        .or_else(|| LOGOS.iter().find(|l| l.ids.contains(&name.as_str())))
+}
+
+/// Picks the logo for the running system: the os-release `ID` first, then each
+/// `ID_LIKE` entry in order, then the platform for systems without os-release.
+pub fn detect(os: Option<&OsInfo>) -> Option<&'static Logo> {
+    let ids = os.into_iter().flat_map(|os| os.id.iter().chain(os.id_like.iter()));
+    for id in ids {
+        if let Some(logo) = find(id) {
+            return Some(logo);
+        }
+    }
+    if cfg!(target_os = "macos") {
+        find("macos")
+    } else if cfg!(target_os = "windows") {
+        find("windows")
+    } else {
+        None
+    }
+}
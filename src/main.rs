@@ -1,11 +1,27 @@
+mod bytes;
+mod cache;
+mod color;
 mod config;
 mod info;
+mod logos;
+mod packages;
+mod rpmdb;
+mod svg;
+mod terminfo;
+mod textart;
+mod timings;
 mod ui;
+mod width;
 
+use crate::cache::Cache;
+use crate::color::{ColorMode, Painter};
 use crate::config::{generate_config, load_config};
-use crate::info::Info;
+use crate::info::{Info, Probe};
+use crate::timings::{Timings, TimingsFormat};
 use crate::ui::draw;
 use clap::Parser;
+use std::io::{self, Write};
+use std::time::Instant;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -13,7 +29,7 @@ use clap::Parser;
     version,
     about,
     long_about = None,
-    after_help = "Examples:\n  xfetch\n  xfetch --config ~/.config/xfetch/config.jsonc\n  xfetch --gen-config"
+    after_help = "Examples:\n  xfetch\n  xfetch --config ~/.config/xfetch/config.jsonc\n  xfetch --gen-config\n  xfetch --list-logos\n  xfetch --json\n  xfetch --color never > fetch.txt\n  xfetch --timings"
 )]
 struct Args {
     /// Path to config file
@@ -23,6 +39,30 @@ struct Args {
     /// Generate a default config.jsonc (pacman layout) and exit
     #[arg(long)]
     gen_config: bool,
+
+    /// List the built-in logos and the os-release IDs they match, then exit
+    #[arg(long)]
+    list_logos: bool,
+
+    /// Print the collected system information as JSON instead of drawing it
+    #[arg(long)]
+    json: bool,
+
+    /// Report how long each probe and drawing stage took (on stderr)
+    #[arg(long, value_enum, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "table")]
+    timings: Option<TimingsFormat>,
+
+    /// When to use colors
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    color: ColorMode,
+
+    /// Do not read or write the module cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Ignore cached module values and store freshly probed ones
+    #[arg(long, conflicts_with = "no_cache")]
+    refresh_cache: bool,
 }
 
 fn main() {
@@ -41,13 +81,51 @@ fn main() {
             }
         }
     }
-    
+
+    if args.list_logos {
+        for logo in logos::LOGOS {
+            println!("{:<10} {}", logo.name, logo.ids.join(", "));
+        }
+        return;
+    }
+
+    let timings = Timings::default();
+    let start = Instant::now();
+
     // Load config
-    let config = load_config(args.config);
+    let config = timings.time("config", || load_config(args.config.clone()));
 
-    // Gather info
-    let info = Info::new();
+    // Gather only what the configured modules (or the full JSON dump) need
+    let probes = if args.json {
+        Probe::ALL.into_iter().collect()
+    } else {
+        Probe::for_config(&config)
+    };
+    let cache = Cache::new(&config, args.no_cache, args.refresh_cache);
+    let info = timings.time("probe.total", || Info::collect(&probes, &config, &cache, &timings));
+
+    if args.json {
+        let json = match serde_json::to_string_pretty(&info) {
+            Ok(json) => json,
+            Err(err) => {
+                eprintln!("Failed to serialize info: {}", err);
+                std::process::exit(1);
+            }
+        };
+        // A closed pipe (`xfetch --json | head`) just ends the output early.
+        if let Err(err) = writeln!(io::stdout().lock(), "{}", json)
+            && err.kind() != io::ErrorKind::BrokenPipe
+        {
+            eprintln!("Failed to write info: {}", err);
+            std::process::exit(1);
+        }
+    } else {
+        // Draw
+        draw(&info, &config, &Painter::new(args.color), &timings);
+    }
 
-    // Draw
-    draw(&info, &config);
+    if let Some(format) = args.timings {
+        timings.record("total", start.elapsed());
+        timings.report(format);
+    }
 }
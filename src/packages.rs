@@ -0,0 +1,452 @@
+use crate::info::PackageCount;
+use crate::rpmdb;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const PACMAN_LOCAL: &str = "/var/lib/pacman/local";
+const DPKG_STATUS: &str = "/var/lib/dpkg/status";
+const APK_INSTALLED: &str = "/lib/apk/db/installed";
+const XBPS_DB_DIR: &str = "/var/db/xbps";
+const PORTAGE_DB_DIR: &str = "/var/db/pkg";
+const RPM_DB_DIRS: &[&str] = &["/var/lib/rpm", "/usr/lib/sysimage/rpm"];
+/// The sqlite, ndb and bdb databases; rpm updates them in place, so the directory mtime stays put.
+const RPM_DB_FILES: &[&str] = &["rpmdb.sqlite", "rpmdb.sqlite-wal", "Packages.db", "Packages"];
+const NIX_CURRENT_SYSTEM: &str = "/run/current-system";
+const NIX_SYSTEM_PROFILE: &str = "/run/current-system/sw";
+const NIX_PROFILES_DIR: &str = "/nix/var/nix/profiles";
+const NIX_DEFAULT_PROFILE: &str = "/nix/var/nix/profiles/default";
+const NIX_PER_USER_PROFILES: &str = "/nix/var/nix/profiles/per-user";
+const FLATPAK_SYSTEM_DIR: &str = "/var/lib/flatpak";
+const SNAP_DIR: &str = "/snap";
+const BREW_CELLARS: &[&str] = &["/opt/homebrew/Cellar", "/usr/local/Cellar", "/home/linuxbrew/.linuxbrew/Cellar"];
+
+/// Package managers xfetch knows about on this platform, in display order.
+pub const MANAGERS: &[&str] = if cfg!(target_os = "linux") {
+    &[
+        "pacman", "dpkg", "rpm", "apk", "xbps", "portage", "nix", "flatpak", "snap", "brew", "cargo", "pipx",
+    ]
+} else if cfg!(target_os = "macos") {
+    &["brew", "nix", "cargo", "pipx"]
+} else if cfg!(target_os = "windows") {
+    &["scoop", "cargo", "pipx"]
+} else {
+    &["cargo", "pipx"]
+};
+
+/// Counts installed packages for every available manager in `managers`
+/// (all of `MANAGERS` when `None`), skipping managers with nothing installed.
+pub fn get_packages_info(managers: Option<&[String]>, timeout: Duration) -> Vec<PackageCount> {
+    selected(managers)
+        .into_iter()
+        .flat_map(|manager| count_manager(manager, timeout))
+        .filter(|p| p.count > 0)
+        .collect()
+}
+
+/// The known managers named in `managers` (all when `None`), in `MANAGERS` order.
+pub fn selected(managers: Option<&[String]>) -> Vec<&'static str> {
+    MANAGERS
+        .iter()
+        .copied()
+        .filter(|m| managers.is_none_or(|wanted| wanted.iter().any(|w| w.eq_ignore_ascii_case(m))))
+        .collect()
+}
+
+/// Files and directories whose modification time invalidates the cached package counts.
+pub fn db_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = [
+        PACMAN_LOCAL,
+        DPKG_STATUS,
+        APK_INSTALLED,
+        XBPS_DB_DIR,
+        SNAP_DIR,
+    ]
+    .iter()
+    .chain(BREW_CELLARS)
+    .map(PathBuf::from)
+    .collect();
+    for dir in RPM_DB_DIRS {
+        paths.extend(RPM_DB_FILES.iter().map(|file| Path::new(dir).join(file)));
+    }
+    // Emerging a package only touches its category directory.
+    paths.push(PathBuf::from(PORTAGE_DB_DIR));
+    paths.extend(portage_categories());
+    paths.extend(nix_generation_paths());
+    paths.push(Path::new(FLATPAK_SYSTEM_DIR).join("app"));
+    paths.push(Path::new(FLATPAK_SYSTEM_DIR).join("runtime"));
+    if let Some(data) = dirs::data_dir() {
+        paths.push(data.join("flatpak/app"));
+        paths.push(data.join("flatpak/runtime"));
+    }
+    paths.push(cargo_home().join(".crates2.json"));
+    paths.push(pipx_venvs());
+    paths.extend(scoop_apps());
+    paths
+}
+
+fn count_manager(manager: &str, timeout: Duration) -> Vec<PackageCount> {
+    let single = |count: Option<usize>| -> Vec<PackageCount> {
+        count
+            .map(|count| PackageCount { manager: manager.to_string(), source: None, count })
+            .into_iter()
+            .collect()
+    };
+    match manager {
+        "pacman" => single(count_pacman()),
+        "dpkg" => single(count_dpkg()),
+        "rpm" => single(count_rpm(timeout)),
+        "apk" => single(count_apk()),
+        "xbps" => single(count_xbps()),
+        "portage" => single(count_portage()),
+        "nix" => count_nix(),
+        "flatpak" => count_flatpak(),
+        "snap" => single(count_snap()),
+        "brew" => single(count_brew()),
+        "cargo" => single(count_cargo()),
+        "pipx" => single(count_pipx()),
+        "scoop" => single(count_scoop()),
+        _ => Vec::new(),
+    }
+}
+
+/// Number of subdirectories of `dir`, or `None` if it cannot be read.
+fn count_dirs(dir: &Path) -> Option<usize> {
+    let entries = fs::read_dir(dir).ok()?;
+    Some(entries.flatten().filter(|e| e.file_type().is_ok_and(|t| t.is_dir())).count())
+}
+
+/// One directory per installed package (plus the `ALPM_DB_VERSION` file).
+fn count_pacman() -> Option<usize> {
+    count_dirs(Path::new(PACMAN_LOCAL))
+}
+
+fn count_dpkg() -> Option<usize> {
+    Some(count_dpkg_str(&fs::read_to_string(DPKG_STATUS).ok()?))
+}
+
+/// Stanzas in the dpkg status file whose `Status` ends in `installed`.
+fn count_dpkg_str(content: &str) -> usize {
+    content
+        .lines()
+        .filter(|l| l.starts_with("Status:") && l.trim_end().ends_with(" installed"))
+        .count()
+}
+
+/// Reads the rpm database (sqlite, ndb or bdb) directly, and only asks `rpm`
+/// when a database exists but could not be read.
+fn count_rpm(timeout: Duration) -> Option<usize> {
+    let dirs: Vec<&Path> = RPM_DB_DIRS.iter().map(Path::new).filter(|d| d.is_dir()).collect();
+    if dirs.is_empty() {
+        return None;
+    }
+    if let Some(count) = dirs.iter().find_map(|d| rpmdb::count_packages(d)) {
+        return Some(count);
+    }
+    let output = crate::info::run_command(Command::new("rpm").arg("-qa"), timeout)?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).lines().count())
+}
+
+fn count_apk() -> Option<usize> {
+    Some(count_apk_str(&fs::read_to_string(APK_INSTALLED).ok()?))
+}
+
+/// Each package record in the apk database starts with a `P:` (name) line.
+fn count_apk_str(content: &str) -> usize {
+    content.lines().filter(|l| l.starts_with("P:")).count()
+}
+
+fn count_xbps() -> Option<usize> {
+    let pkgdb = fs::read_dir(XBPS_DB_DIR)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("pkgdb-") && n.ends_with(".plist"))
+        })?;
+    Some(count_xbps_str(&fs::read_to_string(pkgdb).ok()?))
+}
+
+/// The xbps pkgdb plist holds one dict per package, each with a `state` key.
+fn count_xbps_str(content: &str) -> usize {
+    let mut count = 0;
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+    while let Some(line) = lines.next() {
+        if line == "<key>state</key>" && lines.next() == Some("<string>installed</string>") {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn portage_categories() -> Vec<PathBuf> {
+    let Ok(categories) = fs::read_dir(PORTAGE_DB_DIR) else {
+        return Vec::new();
+    };
+    categories.flatten().map(|c| c.path()).collect()
+}
+
+/// Portage keeps one directory per package under `/var/db/pkg/<category>/`.
+fn count_portage() -> Option<usize> {
+    if !Path::new(PORTAGE_DB_DIR).is_dir() {
+        return None;
+    }
+    Some(portage_categories().iter().filter_map(|c| count_dirs(c)).sum())
+}
+
+/// Packages in each Nix profile that exists, reported per profile: the NixOS
+/// system profile, the multi-user default profile, the user's own profile and the
+/// current home-manager generation.
+fn count_nix() -> Vec<PackageCount> {
+    nix_profiles()
+        .into_iter()
+        .filter_map(|(source, profile)| {
+            let count = nix_profile_packages(&profile)?.len();
+            Some(PackageCount { manager: "nix".to_string(), source: Some(source.to_string()), count })
+        })
+        .collect()
+}
+
+fn nix_profiles() -> Vec<(&'static str, PathBuf)> {
+    let mut profiles = vec![
+        ("system", PathBuf::from(NIX_SYSTEM_PROFILE)),
+        ("default", PathBuf::from(NIX_DEFAULT_PROFILE)),
+    ];
+    let state = state_home();
+    if let Some(home) = dirs::home_dir() {
+        let user = [home.join(".nix-profile")]
+            .into_iter()
+            .chain(state.as_ref().map(|s| s.join("nix/profile")))
+            .find(|p| p.exists());
+        if let Some(user) = user {
+            profiles.push(("user", user));
+        }
+    }
+    let per_user = env::var("USER").ok().map(|u| Path::new(NIX_PER_USER_PROFILES).join(u).join("home-manager"));
+    let home_manager = state
+        .map(|s| s.join("nix/profiles/home-manager"))
+        .into_iter()
+        .chain(per_user)
+        .find(|p| p.exists());
+    if let Some(generation) = home_manager {
+        profiles.push(("home-manager", generation.join("home-path")));
+    }
+    profiles
+}
+
+fn state_home() -> Option<PathBuf> {
+    env::var("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local/state")))
+}
+
+/// Paths that change when a Nix generation is created or switched to: the
+/// profile links themselves and the directories holding the generation links.
+/// Whatever the links point to lives in the store, where every mtime is 1.
+fn nix_generation_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = nix_profiles().into_iter().map(|(_, profile)| profile).collect();
+    paths.push(PathBuf::from(NIX_CURRENT_SYSTEM));
+    paths.push(PathBuf::from(NIX_PROFILES_DIR));
+    if let Ok(user) = env::var("USER") {
+        paths.push(Path::new(NIX_PER_USER_PROFILES).join(user));
+    }
+    paths.extend(state_home().map(|s| s.join("nix/profiles")));
+    paths
+}
+
+/// Distinct store paths a profile (a `buildEnv` of symlinks) links to. Walking the
+/// links avoids spawning `nix-store --query`, which is slow on large systems.
+fn nix_profile_packages(profile: &Path) -> Option<HashSet<String>> {
+    let root = fs::canonicalize(profile).ok()?;
+    let mut packages = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_symlink()
+                && let Ok(target) = fs::read_link(entry.path())
+                && let Ok(rest) = target.strip_prefix("/nix/store")
+                && let Some(name) = rest.components().next()
+            {
+                let name = name.as_os_str().to_string_lossy();
+                if !name.ends_with("-env-manifest.nix") {
+                    packages.insert(name.into_owned());
+                }
+            }
+        }
+    }
+    Some(packages)
+}
+
+/// Installed apps and runtimes, reported separately for the system-wide and the
+/// per-user flatpak installation.
+fn count_flatpak() -> Vec<PackageCount> {
+    let mut installations = vec![("system", PathBuf::from(FLATPAK_SYSTEM_DIR))];
+    if let Some(data) = dirs::data_dir() {
+        installations.push(("user", data.join("flatpak")));
+    }
+    installations
+        .into_iter()
+        .filter_map(|(source, root)| {
+            let apps = count_flatpak_refs(&root.join("app"));
+            let runtimes = count_flatpak_refs(&root.join("runtime"));
+            if apps.is_none() && runtimes.is_none() {
+                return None;
+            }
+            let count = apps.unwrap_or(0) + runtimes.unwrap_or(0);
+            Some(PackageCount { manager: "flatpak".to_string(), source: Some(source.to_string()), count })
+        })
+        .collect()
+}
+
+/// Installed refs under `app/` or `runtime/`, laid out as `<id>/<arch>/<branch>`.
+/// The `current` symlink next to the arch directories is not a separate install.
+fn count_flatpak_refs(dir: &Path) -> Option<usize> {
+    let ids = fs::read_dir(dir).ok()?;
+    let count = ids
+        .flatten()
+        .flat_map(|id| fs::read_dir(id.path()).into_iter().flatten().flatten())
+        .filter(|arch| arch.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|arch| count_dirs(&arch.path()))
+        .sum();
+    Some(count)
+}
+
+/// Every mounted snap lives in `/snap/<name>`; `/snap/bin` only holds wrappers.
+fn count_snap() -> Option<usize> {
+    let entries = fs::read_dir(SNAP_DIR).ok()?;
+    let count = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()) && e.file_name() != "bin")
+        .count();
+    Some(count)
+}
+
+fn count_brew() -> Option<usize> {
+    let mut cellars: Vec<PathBuf> = BREW_CELLARS.iter().map(PathBuf::from).collect();
+    if let Ok(prefix) = env::var("HOMEBREW_PREFIX") {
+        cellars.insert(0, Path::new(&prefix).join("Cellar"));
+    }
+    cellars.iter().find_map(|c| count_dirs(c))
+}
+
+fn cargo_home() -> PathBuf {
+    env::var("CARGO_HOME")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")))
+        .unwrap_or_default()
+}
+
+/// `cargo install` records every installed crate under `installs` in `.crates2.json`.
+fn count_cargo() -> Option<usize> {
+    let content = fs::read_to_string(cargo_home().join(".crates2.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    Some(json.get("installs")?.as_object()?.len())
+}
+
+fn pipx_venvs() -> PathBuf {
+    if let Ok(home) = env::var("PIPX_HOME") {
+        return Path::new(&home).join("venvs");
+    }
+    dirs::data_dir().map(|d| d.join("pipx/venvs")).unwrap_or_default()
+}
+
+/// pipx installs each application into its own virtualenv.
+fn count_pipx() -> Option<usize> {
+    count_dirs(&pipx_venvs())
+}
+
+fn scoop_apps() -> Option<PathBuf> {
+    let root = env::var("SCOOP")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|h| h.join("scoop")))?;
+    Some(root.join("apps"))
+}
+
+/// Scoop installs every app into its own directory under `<scoop root>/apps`.
+fn count_scoop() -> Option<usize> {
+    let count = fs::read_dir(scoop_apps()?)
+        .ok()?
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()) && e.file_name() != "scoop")
+        .count();
+    Some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_installed_dpkg_stanzas() {
+        let status = "\
+Package: bash
+Status: install ok installed
+Description: GNU Bourne Again SHell
+ Status: install ok installed (a description line, not a field)
+
+Package: old-tool
+Status: deinstall ok config-files
+
+Package: gone
+Status: purge ok not-installed
+
+Package: broken
+Status: install reinstreq half-installed
+
+Package: pinned
+Status: hold ok installed
+";
+        assert_eq!(count_dpkg_str(status), 2);
+        assert_eq!(count_dpkg_str(""), 0);
+    }
+
+    #[test]
+    fn counts_apk_package_records() {
+        let installed = "C:Q1abc=\nP:musl\nV:1.2.5-r0\nD:so:libc.musl\n\nC:Q1def=\nP:busybox\nV:1.36.1-r2\nt:P:not-a-name\n";
+        assert_eq!(count_apk_str(installed), 2);
+    }
+
+    #[test]
+    fn counts_installed_xbps_entries() {
+        let pkgdb = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>bash</key>
+	<dict>
+		<key>pkgver</key>
+		<string>bash-5.2_1</string>
+		<key>state</key>
+		<string>installed</string>
+	</dict>
+	<key>half</key>
+	<dict>
+		<key>state</key>
+		<string>unpacked</string>
+	</dict>
+	<key>installed</key>
+	<dict>
+		<key>state</key>
+
+		<string>installed</string>
+	</dict>
+</dict>
+</plist>
+"#;
+        assert_eq!(count_xbps_str(pkgdb), 2);
+    }
+}
@@ -0,0 +1,354 @@
+//! Minimal readers for the three rpm database backends, just enough to count
+//! installed packages without linking sqlite or Berkeley DB or spawning `rpm`.
+
+use crate::bytes::{be_u16, be_u32, le_u16, le_u32};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Counts the packages in the rpm database stored in `dir`, trying the sqlite
+/// (`rpmdb.sqlite`), ndb (`Packages.db`) and Berkeley DB (`Packages`) backends in
+/// the order rpm itself prefers them.
+pub fn count_packages(dir: &Path) -> Option<usize> {
+    let sqlite = dir.join("rpmdb.sqlite");
+    if sqlite.is_file() {
+        // Pending writes live in the WAL until rpm checkpoints them; let the caller fall back to rpm.
+        let wal_pending = fs::metadata(dir.join("rpmdb.sqlite-wal")).is_ok_and(|m| m.len() > 0);
+        if wal_pending {
+            return None;
+        }
+        return count_sqlite(File::open(sqlite).ok()?);
+    }
+    if let Ok(file) = File::open(dir.join("Packages.db")) {
+        return count_ndb(&read_ndb_slots(file)?);
+    }
+    if let Ok(data) = fs::read(dir.join("Packages")) {
+        return count_bdb(&data);
+    }
+    None
+}
+
+// --- sqlite ---
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const SQLITE_HEADER_SIZE: usize = 100;
+
+/// Counts the rows of the `Packages` table by walking its table b-tree. Pages
+/// are read as the walk reaches them, so the package headers, which live in
+/// overflow pages, are never loaded.
+pub fn count_sqlite(file: impl Read + Seek) -> Option<usize> {
+    let mut db = SqliteFile::new(file)?;
+    let mut root = None;
+    db.walk_table(1, &mut |payload| {
+        if root.is_none() {
+            root = schema_table_root(payload, "Packages");
+        }
+    })?;
+    let mut count = 0;
+    db.walk_table(root?, &mut |_| count += 1)?;
+    Some(count)
+}
+
+struct SqliteFile<R> {
+    file: R,
+    pages: usize,
+    page_size: usize,
+    usable_size: usize,
+}
+
+impl<R: Read + Seek> SqliteFile<R> {
+    fn new(mut file: R) -> Option<Self> {
+        let mut header = [0; SQLITE_HEADER_SIZE];
+        file.read_exact(&mut header).ok()?;
+        if !header.starts_with(SQLITE_MAGIC) {
+            return None;
+        }
+        let page_size = match be_u16(&header, 16)? {
+            1 => 65536,
+            n => n as usize,
+        };
+        // The same limits sqlite itself enforces; anything else is not a database it wrote.
+        if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
+            return None;
+        }
+        let usable_size = page_size - header[20] as usize;
+        if usable_size < 480 {
+            return None;
+        }
+        let len = file.seek(SeekFrom::End(0)).ok()?;
+        Some(Self { file, pages: (len / page_size as u64) as usize, page_size, usable_size })
+    }
+
+    fn page(&mut self, number: u32) -> Option<Vec<u8>> {
+        let index = (number as usize).checked_sub(1)?;
+        if index >= self.pages {
+            return None;
+        }
+        let mut page = vec![0; self.page_size];
+        self.file.seek(SeekFrom::Start((index * self.page_size) as u64)).ok()?;
+        self.file.read_exact(&mut page).ok()?;
+        Some(page)
+    }
+
+    /// Calls `on_row` with the local part of every row payload in the table b-tree rooted at `root`.
+    fn walk_table(&mut self, root: u32, on_row: &mut dyn FnMut(&[u8])) -> Option<()> {
+        let mut stack = vec![root];
+        // A corrupt file could contain a cycle; no real table has more pages than the file.
+        let mut budget = self.pages + 1;
+        while let Some(number) = stack.pop() {
+            budget = budget.checked_sub(1)?;
+            let page = &self.page(number)?;
+            // Page 1 starts with the 100 byte database header.
+            let header = if number == 1 { SQLITE_HEADER_SIZE } else { 0 };
+            let kind = *page.get(header)?;
+            let cells = be_u16(page, header + 3)? as usize;
+            match kind {
+                0x05 => {
+                    for i in 0..cells {
+                        let offset = be_u16(page, header + 12 + i * 2)? as usize;
+                        stack.push(be_u32(page, offset)?);
+                    }
+                    stack.push(be_u32(page, header + 8)?);
+                }
+                0x0d => {
+                    for i in 0..cells {
+                        let offset = be_u16(page, header + 8 + i * 2)? as usize;
+                        let (payload_len, n) = varint(page.get(offset..)?)?;
+                        let (_rowid, m) = varint(page.get(offset + n..)?)?;
+                        let start = offset + n + m;
+                        let local = self.local_payload_len(payload_len as usize);
+                        on_row(page.get(start..(start + local).min(page.len()))?);
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(())
+    }
+
+    /// Bytes of a table leaf payload stored on the page itself (the rest spills to overflow pages).
+    fn local_payload_len(&self, payload: usize) -> usize {
+        let max_local = self.usable_size - 35;
+        if payload <= max_local {
+            return payload;
+        }
+        let min_local = (self.usable_size - 12) * 32 / 255 - 23;
+        let local = min_local + (payload - min_local) % (self.usable_size - 4);
+        if local <= max_local { local } else { min_local }
+    }
+}
+
+/// Returns the root page of `table` if `record` is its `sqlite_schema` row
+/// (`type, name, tbl_name, rootpage, sql`).
+fn schema_table_root(record: &[u8], table: &str) -> Option<u32> {
+    let (header_len, mut pos) = varint(record)?;
+    let mut types = Vec::new();
+    while pos < header_len as usize && types.len() < 4 {
+        let (serial, n) = varint(record.get(pos..)?)?;
+        types.push(serial);
+        pos += n;
+    }
+    let mut body = header_len as usize;
+    let mut fields = Vec::new();
+    for serial in types {
+        let len = serial_len(serial)?;
+        fields.push((serial, record.get(body..body + len)?));
+        body += len;
+    }
+    let [(_, kind), (_, name), _, (root_type, root)] = fields.as_slice() else {
+        return None;
+    };
+    if *kind != b"table" || *name != table.as_bytes() {
+        return None;
+    }
+    match root_type {
+        1..=4 => Some(root.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)),
+        _ => None,
+    }
+}
+
+fn serial_len(serial: u64) -> Option<usize> {
+    Some(match serial {
+        0 | 8 | 9 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        5 => 6,
+        6 | 7 => 8,
+        n if n >= 12 => ((n - 12) / 2) as usize,
+        _ => return None,
+    })
+}
+
+/// sqlite's big-endian variable length integer; returns the value and its encoded length.
+fn varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let byte = *bytes.get(i)?;
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+// --- ndb ---
+
+const NDB_MAGIC: u32 = u32::from_le_bytes(*b"RpmP");
+const NDB_SLOT_MAGIC: u32 = u32::from_le_bytes(*b"Slot");
+const NDB_HEADER_SIZE: usize = 32;
+const NDB_SLOT_SIZE: usize = 16;
+const NDB_PAGE_SIZE: usize = 4096;
+
+/// Reads just the slot pages of a `Packages.db`, skipping the package blobs after them.
+fn read_ndb_slots(mut file: File) -> Option<Vec<u8>> {
+    let mut data = vec![0; NDB_HEADER_SIZE];
+    file.read_exact(&mut data).ok()?;
+    let slot_pages = le_u32(&data, 12)? as u64;
+    let rest = (slot_pages * NDB_PAGE_SIZE as u64).saturating_sub(NDB_HEADER_SIZE as u64);
+    file.take(rest).read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+/// Counts used slots in an ndb `Packages.db`: the slot pages at the start of the
+/// file hold one 16 byte slot per package, with a non-zero package index when in use.
+pub fn count_ndb(data: &[u8]) -> Option<usize> {
+    if le_u32(data, 0)? != NDB_MAGIC {
+        return None;
+    }
+    let slot_pages = le_u32(data, 12)? as usize;
+    let slots_end = data.len().min(slot_pages * NDB_PAGE_SIZE);
+    let mut count = 0;
+    let mut offset = NDB_HEADER_SIZE;
+    while offset + NDB_SLOT_SIZE <= slots_end {
+        if le_u32(data, offset)? != NDB_SLOT_MAGIC {
+            return None;
+        }
+        if le_u32(data, offset + 4)? != 0 {
+            count += 1;
+        }
+        offset += NDB_SLOT_SIZE;
+    }
+    Some(count)
+}
+
+// --- Berkeley DB ---
+
+const BDB_HASH_MAGIC: u32 = 0x061561;
+const BDB_PAGE_HEADER_SIZE: usize = 26;
+const BDB_P_HASH_UNSORTED: u8 = 2;
+const BDB_P_HASH: u8 = 13;
+const BDB_H_KEYDATA: u8 = 1;
+
+/// Counts keys in the Berkeley DB hash file rpm used before 4.16. Every hash page
+/// stores key/data pairs; record 0 is rpm's bookkeeping entry, not a package.
+pub fn count_bdb(data: &[u8]) -> Option<usize> {
+    let little_endian = match le_u32(data, 12)? {
+        BDB_HASH_MAGIC => true,
+        _ if be_u32(data, 12)? == BDB_HASH_MAGIC => false,
+        _ => return None,
+    };
+    let read_u16 = |bytes: &[u8], at| if little_endian { le_u16(bytes, at) } else { be_u16(bytes, at) };
+    let read_u32 = |bytes: &[u8], at| if little_endian { le_u32(bytes, at) } else { be_u32(bytes, at) };
+
+    let page_size = read_u32(data, 20)? as usize;
+    if page_size < BDB_PAGE_HEADER_SIZE {
+        return None;
+    }
+    let mut count = 0;
+    for page in data.chunks_exact(page_size).skip(1) {
+        if page[25] != BDB_P_HASH && page[25] != BDB_P_HASH_UNSORTED {
+            continue;
+        }
+        let entries = read_u16(page, 20)? as usize;
+        for pair in 0..entries / 2 {
+            let key_offset = read_u16(page, BDB_PAGE_HEADER_SIZE + pair * 4)? as usize;
+            // Items are packed from the end of the page, so a key ends where the previous item starts.
+            let key_end = match pair {
+                0 => page_size,
+                _ => read_u16(page, BDB_PAGE_HEADER_SIZE + pair * 4 - 2)? as usize,
+            };
+            let key = page.get(key_offset..key_end)?;
+            let is_bookkeeping = key.first() == Some(&BDB_H_KEYDATA) && key.len() == 5 && read_u32(key, 1)? == 0;
+            if !is_bookkeeping {
+                count += 1;
+            }
+        }
+    }
+    Some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpmdb").join(name)
+    }
+
+    #[test]
+    fn counts_sqlite_rows_across_interior_pages() {
+        let file = File::open(fixture("sqlite/rpmdb.sqlite")).unwrap();
+        assert_eq!(count_sqlite(file), Some(120));
+    }
+
+    #[test]
+    fn rejects_bad_sqlite_headers() {
+        let data = fs::read(fixture("sqlite/rpmdb.sqlite")).unwrap();
+        let opens = |patch: &[(usize, u8)]| {
+            let mut data = data.clone();
+            for (at, byte) in patch {
+                data[*at] = *byte;
+            }
+            SqliteFile::new(Cursor::new(data)).is_some()
+        };
+        assert!(opens(&[]));
+        assert!(opens(&[(16, 0x02), (17, 0x00), (20, 32)]));
+        assert!(!opens(&[(16, 0), (17, 0)]));
+        assert!(!opens(&[(16, 0x01), (17, 0x00)]));
+        assert!(!opens(&[(16, 0x03), (17, 0x00)]));
+        assert!(!opens(&[(16, 0x02), (17, 0x00), (20, 33)]));
+        assert!(SqliteFile::new(Cursor::new(&data[..60])).is_none());
+        let mut zeroed = data.clone();
+        zeroed[SQLITE_MAGIC.len()..SQLITE_HEADER_SIZE].fill(0);
+        assert_eq!(count_sqlite(Cursor::new(zeroed)), None);
+    }
+
+    #[test]
+    fn counts_used_ndb_slots() {
+        let data = fs::read(fixture("ndb/Packages.db")).unwrap();
+        assert_eq!(count_ndb(&data), Some(7));
+        let slots = read_ndb_slots(File::open(fixture("ndb/Packages.db")).unwrap()).unwrap();
+        assert_eq!(slots.len(), 2 * NDB_PAGE_SIZE);
+        assert_eq!(count_ndb(&slots), Some(7));
+    }
+
+    #[test]
+    fn counts_bdb_keys_without_bookkeeping_record() {
+        let data = fs::read(fixture("bdb/Packages")).unwrap();
+        assert_eq!(count_bdb(&data), Some(5));
+    }
+
+    #[test]
+    fn picks_backend_from_directory() {
+        assert_eq!(count_packages(&fixture("sqlite")), Some(120));
+        assert_eq!(count_packages(&fixture("ndb")), Some(7));
+        assert_eq!(count_packages(&fixture("bdb")), Some(5));
+        assert_eq!(count_packages(&fixture("missing")), None);
+    }
+
+    #[test]
+    fn rejects_foreign_files() {
+        let data = fs::read(fixture("ndb/Packages.db")).unwrap();
+        assert_eq!(count_sqlite(Cursor::new(&data)), None);
+        assert_eq!(count_bdb(&data), None);
+        assert_eq!(count_ndb(SQLITE_MAGIC), None);
+    }
+}
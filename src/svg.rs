@@ -0,0 +1,47 @@
+//! Rasterizes SVG logos, which the `image` crate (and so viuer) cannot decode.
+
+use image::{DynamicImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use std::fs;
+use std::path::Path;
+
+/// Pixels rendered for every terminal cell across. Graphics protocols scale
+/// the image down to the cells it was given, so this only sets the sharpness.
+const PIXELS_PER_CELL: u32 = 8;
+
+/// Reads the SVG at `path` and returns its size in user units.
+pub fn load(path: &Path) -> Option<(usvg::Tree, (u32, u32))> {
+    let data = fs::read(path).ok()?;
+    let mut options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_path_buf),
+        ..Default::default()
+    };
+    // Loading the system fonts is slow, so only logos with text pay for it.
+    if data.windows(5).any(|w| w == b"<text") {
+        options.fontdb_mut().load_system_fonts();
+    }
+    let tree = usvg::Tree::from_data(&data, &options).ok()?;
+    let size = tree.size();
+    Some((tree, (size.width().ceil() as u32, size.height().ceil() as u32)))
+}
+
+/// Renders `tree` for display `cells` columns wide (and, like every image, two
+/// pixel rows per cell down), keeping its aspect ratio.
+pub fn rasterize(tree: &usvg::Tree, cells: u32) -> Option<DynamicImage> {
+    let size = tree.size();
+    let width = cells.max(1) * PIXELS_PER_CELL;
+    let scale = width as f32 / size.width();
+    let height = ((size.height() * scale).round() as u32).max(1);
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+}
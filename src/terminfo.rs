@@ -0,0 +1,178 @@
+//! Just enough of a compiled terminfo reader to learn how many colors the
+//! terminal supports, without linking ncurses.
+
+use crate::bytes::{le_i16, le_i32, le_u16};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const MAGIC_16BIT: u16 = 0o432;
+const MAGIC_32BIT: u16 = 0o1036;
+/// Index of `colors` among the standard numeric capabilities.
+const MAX_COLORS: usize = 13;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Colors {
+    /// The `colors` capability, if the entry sets it.
+    pub max_colors: Option<u32>,
+    /// Set by the `Tc` or `RGB` extended capabilities that advertise 24-bit color.
+    pub truecolor: bool,
+}
+
+/// Reads the color capabilities of the entry for `term` from the usual terminfo directories.
+pub fn colors(term: &str) -> Option<Colors> {
+    find(&search_dirs(), term)
+}
+
+fn find(dirs: &[PathBuf], term: &str) -> Option<Colors> {
+    let first = term.chars().next()?;
+    if term.contains(['/', '\\']) {
+        return None;
+    }
+    dirs.iter().find_map(|dir| {
+        // Entries live under their first letter, or its hex code on macOS.
+        let lettered = dir.join(first.to_string()).join(term);
+        let hex = dir.join(format!("{:x}", first as u32)).join(term);
+        let data = fs::read(lettered).or_else(|_| fs::read(hex)).ok()?;
+        parse(&data)
+    })
+}
+
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        // An empty element stands for the compiled-in default, which the fixed list below covers.
+        dirs.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    for dir in ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo", "/usr/lib/terminfo"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+/// Parses a compiled entry: a header of six counts, the names, booleans,
+/// numbers, string offsets and string table, then optionally the same again
+/// for user-defined (extended) capabilities.
+pub fn parse(data: &[u8]) -> Option<Colors> {
+    let number_size = match le_u16(data, 0)? {
+        MAGIC_16BIT => 2,
+        MAGIC_32BIT => 4,
+        _ => return None,
+    };
+    let count = |i: usize| le_i16(data, i * 2).map(|n| n.max(0) as usize);
+    let (names, bools, numbers, strings, table) = (count(1)?, count(2)?, count(3)?, count(4)?, count(5)?);
+
+    let mut pos = 12 + names + bools;
+    pos += pos % 2;
+    let read_number = |at: usize| -> Option<i32> {
+        match number_size {
+            2 => le_i16(data, at).map(i32::from),
+            _ => le_i32(data, at),
+        }
+    };
+    let max_colors = (MAX_COLORS < numbers)
+        .then(|| read_number(pos + MAX_COLORS * number_size))
+        .flatten()
+        .filter(|n| *n > 0)
+        .map(|n| n as u32);
+    pos += numbers * number_size + strings * 2 + table;
+
+    Some(Colors { max_colors, truecolor: extended_truecolor(data, pos, number_size).unwrap_or(false) })
+}
+
+/// Looks for `Tc` or `RGB` among the extended capabilities starting at `pos`.
+fn extended_truecolor(data: &[u8], mut pos: usize, number_size: usize) -> Option<bool> {
+    pos += pos % 2;
+    let count = |i: usize| le_i16(data, pos + i * 2).map(|n| n.max(0) as usize);
+    let (bools, numbers, strings, _items, table) = (count(0)?, count(1)?, count(2)?, count(3)?, count(4)?);
+    pos += 10;
+
+    let bool_values = data.get(pos..pos + bools)?;
+    pos += bools;
+    pos += pos % 2;
+    let number_values: Vec<i32> = (0..numbers)
+        .map(|i| match number_size {
+            2 => le_i16(data, pos + i * 2).map(i32::from),
+            _ => le_i32(data, pos + i * 4),
+        })
+        .collect::<Option<_>>()?;
+    pos += numbers * number_size;
+    let names_count = bools + numbers + strings;
+    pos += (strings + names_count) * 2;
+
+    // The table holds the string values followed by every capability name, each
+    // NUL-terminated; the names are therefore its last `names_count` entries.
+    let table = data.get(pos..pos + table)?;
+    let entries: Vec<&[u8]> = table.strip_suffix(&[0]).unwrap_or(table).split(|b| *b == 0).collect();
+    let names = entries.get(entries.len().checked_sub(names_count)?..)?;
+
+    let is_rgb = |name: &[u8]| name == b"Tc" || name == b"RGB";
+    let bool_set = names[..bools].iter().zip(bool_values).any(|(name, value)| is_rgb(name) && *value == 1);
+    let number_set = names[bools..bools + numbers].iter().zip(&number_values).any(|(name, value)| is_rgb(name) && *value > 0);
+    Some(bool_set || number_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/terminfo")
+    }
+
+    fn fixture(path: &str) -> Vec<u8> {
+        fs::read(fixture_dir().join(path)).unwrap()
+    }
+
+    #[test]
+    fn reads_16bit_entry_with_padding_and_extended_caps() {
+        // Odd names + booleans length and a single extended boolean both need a pad byte.
+        let colors = parse(&fixture("l/linux")).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(8), truecolor: false });
+        let colors = parse(&fixture("l/linux-tc")).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(8), truecolor: true });
+    }
+
+    #[test]
+    fn reads_32bit_entries() {
+        let colors = parse(&fixture("x/xterm-256color")).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(256), truecolor: false });
+        let colors = parse(&fixture("x/xterm-tc")).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(256), truecolor: true });
+        let colors = parse(&fixture("x/xterm-direct")).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(0x1000000), truecolor: true });
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_data() {
+        assert_eq!(parse(b"not a terminfo entry"), None);
+        assert_eq!(parse(&[]), None);
+        let data = fixture("x/xterm-tc");
+        assert_eq!(parse(&data[..8]), None);
+        // Losing the extended section only loses the truecolor flag.
+        let count = |i: usize| le_i16(&data, i * 2).unwrap() as usize;
+        let mut standard_end = 12 + count(1) + count(2);
+        standard_end += standard_end % 2;
+        standard_end += count(3) * 4 + count(4) * 2 + count(5);
+        assert!(standard_end < data.len());
+        let colors = parse(&data[..standard_end]).unwrap();
+        assert_eq!(colors, Colors { max_colors: Some(256), truecolor: false });
+    }
+
+    #[test]
+    fn finds_entries_by_first_letter() {
+        let dirs = [fixture_dir().join("missing"), fixture_dir()];
+        assert_eq!(find(&dirs, "xterm-tc").map(|c| c.truecolor), Some(true));
+        assert_eq!(find(&dirs, "linux").and_then(|c| c.max_colors), Some(8));
+        assert_eq!(find(&dirs, "vt100"), None);
+        assert_eq!(find(&dirs, "../l/linux"), None);
+        assert_eq!(find(&dirs, ""), None);
+    }
+}
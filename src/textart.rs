@@ -0,0 +1,170 @@
+//! Turns image logos into colored text: ASCII, Unicode braille or half-blocks.
+//! Unlike terminal graphics, text art survives tmux, SSH and redirected output.
+
+use crate::color::{Color, Painter, Style};
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba};
+
+/// Characters from the faintest to the densest.
+const RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// Braille dot bits, indexed by row then column within the 2×4 cell.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextArt {
+    Ascii,
+    Braille,
+    HalfBlocks,
+}
+
+impl TextArt {
+    pub fn parse(value: &str) -> Option<TextArt> {
+        match value.to_lowercase().as_str() {
+            "ascii" => Some(TextArt::Ascii),
+            "braille" => Some(TextArt::Braille),
+            "half-blocks" => Some(TextArt::HalfBlocks),
+            _ => None,
+        }
+    }
+
+    /// Pixels sampled for every cell, across and down.
+    fn cell_pixels(self) -> (u32, u32) {
+        match self {
+            TextArt::Braille => (2, 4),
+            TextArt::Ascii | TextArt::HalfBlocks => (1, 2),
+        }
+    }
+}
+
+/// Draws `image` as `cols`×`rows` cells of text art.
+pub fn convert(image: &DynamicImage, art: TextArt, cols: u32, rows: u32, p: &Painter) -> Vec<String> {
+    let (across, down) = art.cell_pixels();
+    let pixels = image.resize_exact(cols * across, rows * down, FilterType::Triangle).to_rgba8();
+    // Braille dots are set where a pixel is brighter than the image on average.
+    let threshold = pixels.pixels().map(|px| level(*px)).sum::<f64>() / (pixels.len() / 4).max(1) as f64;
+
+    (0..rows)
+        .map(|row| {
+            let cells = (0..cols).map(|col| {
+                let cell: Vec<Vec<Rgba<u8>>> = (0..down)
+                    .map(|y| (0..across).map(|x| *pixels.get_pixel(col * across + x, row * down + y)).collect())
+                    .collect();
+                match art {
+                    TextArt::Ascii => ascii_cell(&cell),
+                    TextArt::Braille => braille_cell(&cell, threshold),
+                    TextArt::HalfBlocks => half_block_cell(&cell, p),
+                }
+            });
+            paint_cells(cells, p)
+        })
+        .collect()
+}
+
+/// How much a pixel stands out on a dark terminal, from 0 to 1.
+fn level(px: Rgba<u8>) -> f64 {
+    let [r, g, b, a] = px.0.map(|c| c as f64 / 255.0);
+    (0.2126 * r + 0.7152 * g + 0.0722 * b) * a
+}
+
+/// The average color of `pixels`, weighted by their opacity.
+fn average<'a>(pixels: impl Iterator<Item = &'a Rgba<u8>>) -> Option<Color> {
+    let (mut r, mut g, mut b, mut total) = (0.0, 0.0, 0.0, 0.0);
+    for px in pixels {
+        let a = px[3] as f64;
+        r += px[0] as f64 * a;
+        g += px[1] as f64 * a;
+        b += px[2] as f64 * a;
+        total += a;
+    }
+    (total > 0.0).then(|| Color::Rgb((r / total) as u8, (g / total) as u8, (b / total) as u8))
+}
+
+fn ascii_cell(cell: &[Vec<Rgba<u8>>]) -> (char, Style) {
+    let pixels: Vec<&Rgba<u8>> = cell.iter().flatten().collect();
+    let value = pixels.iter().map(|px| level(**px)).sum::<f64>() / pixels.len() as f64;
+    let ch = RAMP[(value * (RAMP.len() - 1) as f64).round() as usize];
+    match average(pixels.into_iter()) {
+        Some(color) if ch != ' ' => (ch, Style::fg(color)),
+        _ => (' ', Style::default()),
+    }
+}
+
+fn braille_cell(cell: &[Vec<Rgba<u8>>], threshold: f64) -> (char, Style) {
+    let mut bits = 0;
+    let mut lit = Vec::new();
+    for (y, row) in cell.iter().enumerate() {
+        for (x, px) in row.iter().enumerate() {
+            if px[3] >= 128 && level(*px) > threshold {
+                bits |= BRAILLE_DOTS[y][x];
+                lit.push(px);
+            }
+        }
+    }
+    match average(lit.into_iter()) {
+        Some(color) => (char::from_u32(0x2800 + bits).unwrap_or(' '), Style::fg(color)),
+        None => (' ', Style::default()),
+    }
+}
+
+fn half_block_cell(cell: &[Vec<Rgba<u8>>], p: &Painter) -> (char, Style) {
+    let visible = |px: &Rgba<u8>| (px[3] >= 128).then(|| Color::Rgb(px[0], px[1], px[2]));
+    match (visible(&cell[0][0]), visible(&cell[1][0])) {
+        // Without colors there is no background to draw the lower half with.
+        (Some(_), Some(_)) if !p.enabled() => ('█', Style::default()),
+        (Some(top), Some(bottom)) => ('▀', Style { bg: Some(bottom), ..Style::fg(top) }),
+        (Some(top), None) => ('▀', Style::fg(top)),
+        (None, Some(bottom)) => ('▄', Style::fg(bottom)),
+        (None, None) => (' ', Style::default()),
+    }
+}
+
+/// Joins styled cells into a line, opening a new style only where it changes.
+fn paint_cells(cells: impl Iterator<Item = (char, Style)>, p: &Painter) -> String {
+    p.paint_runs(cells.map(|(ch, style)| (ch, Some(style)))).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorMode;
+    use image::RgbaImage;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    fn image(width: u32, height: u32, pixels: &[Rgba<u8>]) -> DynamicImage {
+        let mut img = RgbaImage::new(width, height);
+        for (px, value) in img.pixels_mut().zip(pixels) {
+            *px = *value;
+        }
+        DynamicImage::ImageRgba8(img)
+    }
+
+    #[test]
+    fn half_blocks_follow_transparency() {
+        let p = Painter::new(ColorMode::Never);
+        let img = image(4, 2, &[WHITE, WHITE, CLEAR, CLEAR, WHITE, CLEAR, WHITE, CLEAR]);
+        assert_eq!(convert(&img, TextArt::HalfBlocks, 4, 1, &p), ["█▀▄"]);
+    }
+
+    #[test]
+    fn braille_sets_dots_for_bright_pixels() {
+        let p = Painter::new(ColorMode::Never);
+        let mut pixels = vec![CLEAR; 8];
+        pixels[0] = WHITE; // top left dot
+        pixels[7] = WHITE; // bottom right dot
+        assert_eq!(convert(&image(2, 4, &pixels), TextArt::Braille, 1, 1, &p), ["\u{2881}"]);
+    }
+
+    #[test]
+    fn ascii_ramps_with_brightness_and_colors_runs() {
+        let p = Painter::new(ColorMode::Never);
+        assert_eq!(convert(&image(1, 2, &[WHITE, WHITE]), TextArt::Ascii, 1, 1, &p), ["@"]);
+        assert_eq!(convert(&image(1, 2, &[CLEAR, CLEAR]), TextArt::Ascii, 1, 1, &p), [""]);
+
+        let p = Painter::new(ColorMode::Always);
+        let line = &convert(&image(2, 2, &[WHITE; 4]), TextArt::Ascii, 2, 1, &p)[0];
+        assert_eq!(line.matches('\x1b').count(), 2, "{:?}", line);
+    }
+}
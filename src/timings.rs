@@ -0,0 +1,63 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimingsFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Timing {
+    pub stage: String,
+    pub ms: f64,
+}
+
+/// Collects how long each stage of a run took. Shared by reference across probe threads.
+#[derive(Debug, Default)]
+pub struct Timings {
+    entries: Mutex<Vec<Timing>>,
+}
+
+impl Timings {
+    pub fn record(&self, stage: &str, elapsed: Duration) {
+        if let Ok(mut entries) = self.entries.lock() {
+            // Microsecond precision is plenty and keeps the JSON readable.
+            let ms = (elapsed.as_secs_f64() * 1_000_000.0).round() / 1000.0;
+            entries.push(Timing { stage: stage.to_string(), ms });
+        }
+    }
+
+    /// Runs `f` and records its duration under `stage`.
+    pub fn time<T>(&self, stage: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.record(stage, start.elapsed());
+        value
+    }
+
+    /// Writes the collected timings to stderr so they never mix with the fetch output.
+    pub fn report(&self, format: TimingsFormat) {
+        let entries = match self.entries.lock() {
+            Ok(entries) => entries.clone(),
+            Err(_) => return,
+        };
+
+        match format {
+            TimingsFormat::Json => {
+                if let Ok(json) = serde_json::to_string_pretty(&entries) {
+                    eprintln!("{}", json);
+                }
+            }
+            TimingsFormat::Table => {
+                let width = entries.iter().map(|t| t.stage.len()).max().unwrap_or(0).max("stage".len());
+                eprintln!("{:<width$}  {:>10}", "stage", "ms", width = width);
+                for t in &entries {
+                    eprintln!("{:<width$}  {:>10.3}", t.stage, t.ms, width = width);
+                }
+            }
+        }
+    }
+}
@@ -1,17 +1,26 @@
 use crate::config::{Config, ModuleConfig};
-use crate::info::Info;
-use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crate::color::{gradient, rainbow, Color, Painter, Style};
+use crate::logos;
+use crate::svg;
+use crate::textart::{self, TextArt};
+use crate::timings::Timings;
+use crate::width::{display_width, truncate, wrap, DEFAULT_NERD_FONT_WIDTH};
+use crate::info::{BatteryInfo, CpuInfo, DiskInfo, Info, MemoryInfo, OsInfo, PackageCount};
+use crossterm::style::Print;
 use crossterm::execute;
-use std::io::stdout;
-use viuer::{print_from_file, Config as ViuerConfig};
-use std::path::PathBuf;
+use std::io::{self, stdout, IsTerminal, Stdout};
+use image::DynamicImage;
+use viuer::Config as ViuerConfig;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use console::strip_ansi_codes;
 
 fn expand_path(path: &str) -> PathBuf {
-    if path.starts_with("~") {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(&path[2..]);
-        }
+    if let Some(rest) = path.strip_prefix("~")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest.trim_start_matches(['/', '\\']));
     }
     PathBuf::from(path)
 }
@@ -22,36 +31,282 @@ enum RenderNode {
     Group { title: String, children: Vec<RenderNode> },
 }
 
-pub fn draw(info: &Info, config: &Config) {
+/// Space between the logo and the info column.
+const GAP: &str = "  ";
+
+/// Width of the terminal stdout is attached to; unknown when redirected.
+fn terminal_width() -> Option<usize> {
+    if !stdout().is_terminal() {
+        return None;
+    }
+    crossterm::terminal::size().ok().map(|(cols, _)| cols as usize).filter(|c| *c > 0)
+}
+
+/// Narrowest info column worth keeping next to the logo; below it the logo moves or shrinks.
+const MIN_INFO_WIDTH: usize = 40;
+
+/// The configured logo, ready to print.
+enum LogoArt {
+    /// Painted lines, plus the compact variant of a built-in logo.
+    Text { lines: Vec<String>, compact: Option<Vec<String>> },
+    /// An image printed by viuer, with the width in cells it is drawn at and
+    /// how (size and protocol) viuer should draw it.
+    Image { image: DynamicImage, width: usize, conf: ViuerConfig },
+}
+
+/// Where the logo goes relative to the info (`logo_position`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogoPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Hidden,
+}
+
+impl LogoPosition {
+    fn from_config(config: &Config) -> Self {
+        match config.logo_position.as_deref().map(str::to_lowercase).as_deref() {
+            Some("right") => LogoPosition::Right,
+            Some("top") => LogoPosition::Top,
+            Some("bottom") => LogoPosition::Bottom,
+            Some("none") => LogoPosition::Hidden,
+            _ => LogoPosition::Left,
+        }
+    }
+
+    fn is_beside(self) -> bool {
+        matches!(self, LogoPosition::Left | LogoPosition::Right)
+    }
+}
+
+pub fn draw(info: &Info, config: &Config, p: &Painter, timings: &Timings) {
     let mut stdout = stdout();
 
     // Prepare Render Tree
-    let nodes = prepare_render_tree(info, &config.modules, config);
+    let nodes = timings.time("draw.modules", || prepare_render_tree(info, &config.modules, config, p));
+
+    let nf = nerd_font_width(config);
+    let position = LogoPosition::from_config(config);
 
     // ASCII/Image handling
+    let logo = match position {
+        LogoPosition::Hidden => None,
+        _ => timings.time("draw.logo", || load_logo(info, config, p)),
+    };
+    let text_width = |lines: &[String]| lines.iter().map(|l| display_width(l, nf)).max().unwrap_or(0);
+    let (logo_width, compact_width) = match &logo {
+        Some(LogoArt::Text { lines, compact }) => (text_width(lines), compact.as_deref().map(text_width)),
+        Some(LogoArt::Image { width, .. }) => (*width, None),
+        None => (0, None),
+    };
+
+    // Render content to lines based on layout
+    let layout_type = config.layout.as_deref().unwrap_or("default");
+    let render = |width: Option<usize>| {
+        let lines = match layout_type {
+            "side-block" => render_side_block(&nodes, config, width, p),
+            "tree" => render_tree(&nodes, config, p), // Image 2 style
+            "section" => render_section(&nodes, config, p), // Image 3/4 style
+            "pacman" | "box" | "line" | "dots" | "bottom_line" => render_classic_variants(&nodes, config, layout_type, width, p),
+            _ => render_classic(&nodes, config, p),
+        };
+        // Boxed layouts wrap to fit already; everything else is cut off with an ellipsis.
+        match width {
+            Some(width) => lines.iter().map(|l| truncate(l, width, nf)).collect(),
+            None => lines,
+        }
+    };
+    let total_width = config.max_width.or_else(terminal_width);
+    let (content_lines, placement, compact) = timings.time("draw.layout", || {
+        let natural = text_width(&render(None));
+        let (placement, compact) = match &logo {
+            Some(_) => place_logo(config, position, total_width, logo_width, compact_width, natural),
+            None => (LogoPosition::Hidden, false),
+        };
+        // Columns left for the info, when the output width is known
+        let logo_columns = if compact { compact_width.unwrap_or(0) } else { logo_width };
+        let width = match placement.is_beside() {
+            true => total_width.map(|w| w.saturating_sub(logo_columns + GAP.len())),
+            false => total_width,
+        };
+        (render(width), placement, compact)
+    });
+
+    let output_start = Instant::now();
+    // A closed pipe (`xfetch | head`) just ends the output early.
+    let _ = match logo {
+        Some(LogoArt::Text { lines, compact: small }) => {
+            let lines = if compact { small.unwrap_or(lines) } else { lines };
+            print_text_logo(&mut stdout, placement, &lines, &content_lines, nf)
+        }
+        Some(LogoArt::Image { image, conf, .. }) => print_image_logo(&mut stdout, placement, &image, &conf, &content_lines, nf),
+        None => print_stacked(&mut stdout, &content_lines, &[]),
+    };
+    timings.record("draw.output", output_start.elapsed());
+}
+
+/// Decides where the logo goes and whether to use its compact variant. Without
+/// a known width it always goes where configured; otherwise the `logo_*_below`
+/// thresholds apply first, then whichever arrangement still fits. A logo beside
+/// the info falls back to the top.
+fn place_logo(
+    config: &Config,
+    position: LogoPosition,
+    total: Option<usize>,
+    full: usize,
+    compact: Option<usize>,
+    content: usize,
+) -> (LogoPosition, bool) {
+    let Some(total) = total else {
+        return (position, false);
+    };
+    let below = |threshold: Option<usize>| threshold.is_some_and(|t| total < t);
+    if below(config.logo_hide_below) {
+        return (LogoPosition::Hidden, false);
+    }
+    let force_compact = compact.is_some() && below(config.logo_compact_below);
+    let logo = if force_compact { compact.unwrap_or(full) } else { full };
+    let stacked = match position {
+        LogoPosition::Bottom => LogoPosition::Bottom,
+        _ => LogoPosition::Top,
+    };
+    if !position.is_beside() || below(config.logo_stack_below) {
+        return if logo <= total { (stacked, force_compact) } else { fit_stacked(stacked, total, full, compact) };
+    }
+
+    let fits_beside = |width: usize| width + GAP.len() + content.min(MIN_INFO_WIDTH) <= total;
+    if fits_beside(logo) {
+        return (position, force_compact);
+    }
+    if let Some(small) = compact
+        && fits_beside(small)
+    {
+        return (position, true);
+    }
+    // Narrow panes tend to be short too, so a stacked logo is compact where possible.
+    if let Some(small) = compact
+        && small <= total
+    {
+        return (stacked, true);
+    }
+    fit_stacked(stacked, total, full, compact)
+}
+
+/// A logo above or below the info, in whichever variant fits `total` columns.
+fn fit_stacked(position: LogoPosition, total: usize, full: usize, compact: Option<usize>) -> (LogoPosition, bool) {
+    if full <= total {
+        (position, false)
+    } else if compact.is_some_and(|small| small <= total) {
+        (position, true)
+    } else {
+        (LogoPosition::Hidden, false)
+    }
+}
+
+fn print_text_logo(
+    stdout: &mut Stdout,
+    placement: LogoPosition,
+    logo_lines: &[String],
+    content_lines: &[String],
+    nf: usize,
+) -> io::Result<()> {
+    match placement {
+        LogoPosition::Left => {
+            let width = logo_lines.iter().map(|l| display_width(l, nf)).max().unwrap_or(0);
+            print_columns(stdout, logo_lines, width, false, content_lines, nf)
+        }
+        LogoPosition::Right => {
+            let width = content_lines.iter().map(|l| display_width(l, nf)).max().unwrap_or(0);
+            print_columns(stdout, content_lines, width, false, logo_lines, nf)
+        }
+        LogoPosition::Top => print_stacked(stdout, logo_lines, content_lines),
+        LogoPosition::Bottom => print_stacked(stdout, content_lines, logo_lines),
+        LogoPosition::Hidden => print_stacked(stdout, content_lines, &[]),
+    }
+}
+
+fn print_image_logo(
+    stdout: &mut Stdout,
+    placement: LogoPosition,
+    image: &DynamicImage,
+    conf: &ViuerConfig,
+    content_lines: &[String],
+    nf: usize,
+) -> io::Result<()> {
+    match placement {
+        LogoPosition::Left => match print_image(image, conf, 0, 0) {
+            Some((width, height)) => {
+                execute!(stdout, crossterm::cursor::MoveUp(height as u16))?;
+                print_columns(stdout, &[], width as usize, true, content_lines, nf)?;
+                // Step past the rest of an image taller than the info.
+                for _ in content_lines.len()..height as usize {
+                    execute!(stdout, Print("\n"))?;
+                }
+                Ok(())
+            }
+            None => print_stacked(stdout, content_lines, &[]),
+        },
+        LogoPosition::Right => {
+            print_stacked(stdout, content_lines, &[])?;
+            // Go back up to the first info line and draw the image to the right of the info.
+            let rows = content_lines.len();
+            let width = content_lines.iter().map(|l| display_width(l, nf)).max().unwrap_or(0);
+            if let Some((_, height)) = print_image(image, conf, (width + GAP.len()) as u16, -(rows as i16)) {
+                for _ in height as usize..rows {
+                    execute!(stdout, Print("\n"))?;
+                }
+            }
+            Ok(())
+        }
+        LogoPosition::Top => {
+            if print_image(image, conf, 0, 0).is_some() {
+                execute!(stdout, Print("\n"))?;
+            }
+            print_stacked(stdout, content_lines, &[])
+        }
+        LogoPosition::Bottom => {
+            print_stacked(stdout, content_lines, &[])?;
+            execute!(stdout, Print("\n"))?;
+            print_image(image, conf, 0, 0);
+            Ok(())
+        }
+        LogoPosition::Hidden => print_stacked(stdout, content_lines, &[]),
+    }
+}
+
+/// Loads and paints the configured logo: an image, a text file, or a built-in logo.
+fn load_logo(info: &Info, config: &Config, p: &Painter) -> Option<LogoArt> {
     let mut ascii_lines: Vec<String> = Vec::new();
-    let mut image_printed = false;
-    let mut ascii_width = 0;
+    let mut compact_lines = None;
+    // Custom ASCII files carry their own colors; built-in logos are painted in one.
+    let mut logo_color = None;
+    let mut builtin_logo = false;
 
     if let Some(path_str) = &config.logo_path {
         let path = expand_path(path_str);
         if path_str.ends_with(".png") || path_str.ends_with(".jpg") || path_str.ends_with(".jpeg") || path_str.ends_with(".svg") {
-            let conf = ViuerConfig {
-                absolute_offset: false,
-                transparent: true,
-                ..Default::default()
-            };
-            if let Ok((width, height)) = print_from_file(&path, &conf) {
-                image_printed = true;
-                ascii_width = width as usize;
-                execute!(stdout, crossterm::cursor::MoveUp(height as u16)).unwrap();
+            match load_image(&path, config) {
+                Some((image, conf)) => {
+                    let (cols, rows) = (conf.width.unwrap_or(0), conf.height.unwrap_or(0));
+                    let art = config.logo_protocol.as_deref().and_then(TextArt::parse).or_else(|| {
+                        // Graphics and cursor movement would corrupt redirected output, so it gets text.
+                        let art = if p.enabled() { TextArt::HalfBlocks } else { TextArt::Ascii };
+                        (!stdout().is_terminal()).then_some(art)
+                    });
+                    if let Some(art) = art {
+                        let lines = textart::convert(&image, art, cols, rows, p);
+                        return Some(LogoArt::Text { lines, compact: None });
+                    }
+                    return Some(LogoArt::Image { image, width: cols as usize, conf });
+                }
+                // An image that cannot be read or decoded gives way to the distro logo.
+                None => builtin_logo = true,
+            }
+        } else if let Ok(content) = std::fs::read_to_string(&path) {
+            for line in content.lines() {
+                ascii_lines.push(line.to_string());
             }
-        } else {
-             if let Ok(content) = std::fs::read_to_string(&path) {
-                 for line in content.lines() {
-                     ascii_lines.push(line.to_string());
-                 }
-             }
         }
     } else if let Some(path_str) = &config.ascii {
         let path = expand_path(path_str);
@@ -61,92 +316,197 @@ pub fn draw(info: &Info, config: &Config) {
              }
         }
     } else {
-        let default_art = get_default_ascii();
-        for line in default_art.lines() {
+        builtin_logo = true;
+    }
+
+    if builtin_logo {
+        let builtin = match &config.logo {
+            Some(name) => logos::find(name),
+            None => logos::detect(info.os.as_ref()),
+        };
+        let (art, compact) = match builtin {
+            Some(logo) => {
+                let (r, g, b) = logo.color;
+                logo_color = Some(Color::Rgb(r, g, b));
+                (logo.art.to_string(), logo.small.map(str::to_string))
+            }
+            None => {
+                logo_color = Some(Color::Rgb(128, 128, 128));
+                let art = get_default_ascii();
+                // The big X on top of the key block stands on its own.
+                let compact = art.lines().take(6).collect::<Vec<_>>().join("\n");
+                (art, Some(compact))
+            }
+        };
+        for line in art.lines() {
             ascii_lines.push(line.to_string());
         }
+        compact_lines = compact.map(|c| c.lines().map(str::to_string).collect::<Vec<_>>());
     }
 
-    if !image_printed && !ascii_lines.is_empty() {
+    if ascii_lines.is_empty() {
+        return None;
+    }
+    let paint = |lines: Vec<String>| -> Vec<String> {
         // Trim trailing spaces from ascii lines to avoid excessive width
-        ascii_lines = ascii_lines.into_iter().map(|l| l.trim_end().to_string()).collect();
-        // Use console::measure_text_width to get accurate display width (handling wide chars correctly)
-        ascii_width = ascii_lines.iter().map(|l| console::measure_text_width(l)).max().unwrap_or(0);
+        let lines: Vec<String> = lines.into_iter().map(|l| l.trim_end().to_string()).collect();
+        let logo_colors = config.logo_colors.as_deref().unwrap_or_default();
+        match config.logo_style.as_deref() {
+            Some(style) => {
+                let mut stops: Vec<Color> = logo_colors.iter().filter_map(|c| Color::parse(c)).collect();
+                if stops.is_empty() {
+                    stops.extend(logo_color);
+                }
+                paint_logo(lines, style, &stops, p)
+            }
+            None => {
+                let lines = apply_logo_colors(lines, logo_colors, p);
+                match logo_color {
+                    Some(color) => lines.into_iter().map(|l| p.color(color, l)).collect(),
+                    // Escape codes embedded in custom logo files go too when colors are off.
+                    None if !p.enabled() => lines.iter().map(|l| strip_ansi_codes(l).to_string()).collect(),
+                    None => lines,
+                }
+            }
+        }
+    };
+    Some(LogoArt::Text { lines: paint(ascii_lines), compact: compact_lines.map(paint) })
+}
+
+/// Decodes an image logo and works out how viuer should draw it. SVGs are
+/// rasterized for exactly the cells they are drawn in.
+fn load_image(path: &Path, config: &Config) -> Option<(DynamicImage, ViuerConfig)> {
+    let (image, (width, height)) = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg")) {
+        let (tree, (width, height)) = svg::load(path)?;
+        let cells = image_cells(width, height, config)?;
+        (svg::rasterize(&tree, cells.0)?, cells)
+    } else {
+        let image = image::open(path).ok()?;
+        let cells = image_cells(image.width(), image.height(), config)?;
+        (image, cells)
+    };
+    let mut conf = ViuerConfig {
+        absolute_offset: false,
+        transparent: true,
+        width: Some(width),
+        height: Some(height),
+        ..Default::default()
+    };
+    // Without a protocol set, viuer uses iTerm or Kitty graphics when the terminal has them.
+    conf.use_sixel = false;
+    match config.logo_protocol.as_deref().map(str::to_lowercase).as_deref() {
+        Some("kitty") => conf.use_iterm = false,
+        Some("iterm") => conf.use_kitty = false,
+        Some("sixel") => (conf.use_kitty, conf.use_iterm, conf.use_sixel) = (false, false, true),
+        _ => {}
     }
+    Some((image, conf))
+}
 
-    // Render content to lines based on layout
-    let layout_type = config.layout.as_deref().unwrap_or("default");
-    let content_lines = match layout_type {
-        "side-block" => render_side_block(&nodes, config),
-        "tree" => render_tree(&nodes, config), // Image 2 style
-        "section" => render_section(&nodes, config), // Image 3/4 style
-        "pacman" | "box" | "line" | "dots" | "bottom_line" => render_classic_variants(&nodes, config, layout_type),
-        _ => render_classic(&nodes, config),
+/// Cells across and down for an image of `width`×`height` pixels, counting one
+/// pixel across and two down per cell. `logo_width` and `logo_height` bound
+/// the size (scaling up if need be); otherwise the image is only ever scaled
+/// down to fit the terminal.
+fn image_cells(width: u32, height: u32, config: &Config) -> Option<(u32, u32)> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let aspect = width as f64 / (height as f64 / 2.0);
+    let rows_for = |cols: u32| ((cols as f64 / aspect).round() as u32).max(1);
+    let cols_for = |rows: u32| ((rows as f64 * aspect).round() as u32).max(1);
+    let (max_cols, max_rows) = match (config.logo_width, config.logo_height) {
+        (None, None) => {
+            let (cols, rows) = crossterm::terminal::size().map(|(c, r)| (c as u32, r as u32)).unwrap_or((80, 24));
+            (width.min(cols), (height / 2).min(rows))
+        }
+        (cols, rows) => (cols.unwrap_or(u32::MAX).max(1), rows.unwrap_or(u32::MAX).max(1)),
     };
+    // Scale by whichever side runs out first.
+    if rows_for(max_cols) <= max_rows {
+        Some((max_cols, rows_for(max_cols)))
+    } else {
+        Some((cols_for(max_rows).min(max_cols), max_rows))
+    }
+}
 
-    let max_lines = std::cmp::max(ascii_lines.len(), content_lines.len());
-    let gap = "  ";
+/// Prints an image logo `x` columns right of and `y` rows below (or above,
+/// when negative) the cursor, returning its size in cells. If the graphics
+/// protocol fails, the image is drawn with half-blocks instead.
+fn print_image(image: &DynamicImage, conf: &ViuerConfig, x: u16, y: i16) -> Option<(u32, u32)> {
+    let conf = ViuerConfig { x, y, ..conf.clone() };
+    viuer::print(image, &conf).ok().or_else(|| {
+        let blocks = ViuerConfig { use_kitty: false, use_iterm: false, use_sixel: false, ..conf };
+        viuer::print(image, &blocks).ok()
+    })
+}
+
+/// Prints one block of lines, then the other after a blank line; either may be empty.
+fn print_stacked(stdout: &mut Stdout, first: &[String], second: &[String]) -> io::Result<()> {
+    for line in first {
+        execute!(stdout, Print(line), Print("\n"))?;
+    }
+    if !first.is_empty() && !second.is_empty() {
+        execute!(stdout, Print("\n"))?;
+    }
+    for line in second {
+        execute!(stdout, Print(line), Print("\n"))?;
+    }
+    Ok(())
+}
+
+/// Prints two columns side by side: the left one padded to `left_width` (or,
+/// for an image already on screen, skipped over), then the right one.
+fn print_columns(
+    stdout: &mut Stdout,
+    left_lines: &[String],
+    left_width: usize,
+    left_is_image: bool,
+    right_lines: &[String],
+    nf: usize,
+) -> io::Result<()> {
+    let max_lines = std::cmp::max(left_lines.len(), right_lines.len());
 
     for i in 0..max_lines {
-        // 1. Print Logo Part
-        if image_printed {
-            execute!(stdout, crossterm::cursor::MoveRight(ascii_width as u16)).unwrap();
-            execute!(stdout, Print(gap)).unwrap();
+        // 1. Print Left Part
+        if left_is_image {
+            execute!(stdout, crossterm::cursor::MoveRight(left_width as u16))?;
         } else {
-            let ascii_line = if i < ascii_lines.len() {
-                &ascii_lines[i]
-            } else {
-                ""
-            };
-            let is_custom_ascii = config.ascii.is_some() || config.logo_path.is_some();
-            if is_custom_ascii {
-                 // Calculate padding needed: width - visible_width(ascii_line)
-                 let visible_len = console::measure_text_width(ascii_line);
-                 let padding = if ascii_width > visible_len { ascii_width - visible_len } else { 0 };
-                 execute!(stdout, Print(format!("{}{}", ascii_line, " ".repeat(padding)))).unwrap();
-            } else {
-                 // Calculate padding needed
-                 let visible_len = console::measure_text_width(ascii_line);
-                 let padding = if ascii_width > visible_len { ascii_width - visible_len } else { 0 };
-                 
-                 execute!(
-                    stdout,
-                    SetForegroundColor(Color::Rgb { r: 128, g: 128, b: 128 }),
-                    Print(format!("{}{}", ascii_line, " ".repeat(padding))),
-                    ResetColor
-                ).unwrap();
-            }
-            execute!(stdout, Print(gap)).unwrap();
+            let left_line = left_lines.get(i).map(|l| l.as_str()).unwrap_or("");
+            // Calculate padding needed: width - visible_width(left_line)
+            let visible_len = display_width(left_line, nf);
+            let padding = left_width.saturating_sub(visible_len);
+            execute!(stdout, Print(format!("{}{}", left_line, " ".repeat(padding))))?;
         }
 
-        // 2. Print Info Part
-        if i < content_lines.len() {
-            execute!(stdout, Print(&content_lines[i])).unwrap();
+        // 2. Print Right Part
+        if let Some(line) = right_lines.get(i) {
+            execute!(stdout, Print(GAP), Print(line))?;
         }
-        execute!(stdout, Print("\n")).unwrap();
+        execute!(stdout, Print("\n"))?;
     }
+    Ok(())
 }
 
-fn prepare_render_tree(info: &Info, modules: &[ModuleConfig], config: &Config) -> Vec<RenderNode> {
+fn prepare_render_tree(info: &Info, modules: &[ModuleConfig], config: &Config, p: &Painter) -> Vec<RenderNode> {
     let mut nodes = Vec::new();
     for module in modules {
         match module {
             ModuleConfig::Simple(key) => {
                 if key == "palette" {
-                    let val = format_palette(config);
+                    let val = format_palette(config, p);
                     // Icon for palette is optional, can be "Colors" or empty string if user wants no icon
                     let icon = config.icons.get(key).cloned().unwrap_or("🎨".to_string());
                     nodes.push(RenderNode::Line { key: key.clone(), value: val, icon });
                 } else {
                     let val = get_module_value(info, key);
                     if let Some(v) = val {
-                        let icon = config.icons.get(key).cloned().unwrap_or("●".to_string());
+                        let icon = module_setting(&config.icons, key).cloned().unwrap_or("●".to_string());
                         nodes.push(RenderNode::Line { key: key.clone(), value: v, icon });
                     }
                 }
             },
             ModuleConfig::Group { title, modules } => {
-                let children = prepare_render_tree(info, modules, config);
+                let children = prepare_render_tree(info, modules, config, p);
                 if !children.is_empty() {
                     nodes.push(RenderNode::Group { title: title.clone(), children });
                 }
@@ -158,51 +518,139 @@ fn prepare_render_tree(info: &Info, modules: &[ModuleConfig], config: &Config) -
 
 fn get_module_value(info: &Info, key: &str) -> Option<String> {
     match key {
-        "os" => Some(info.os.clone()),
-        "kernel" => Some(info.kernel.clone()),
-        "hostname" | "host" => Some(info.host_name.clone()),
-        "wm" => Some(info.desktop.clone()),
-        "packages" => Some(info.packages.clone()),
-        "shell" => Some(info.shell.clone()),
-        "cpu" => Some(info.cpu.clone()),
+        "os" => Some(info.os.as_ref().map(format_os).unwrap_or("Unknown".to_string())),
+        key if key.starts_with("os.") => format_os_field(info.os.as_ref(), &key[3..]),
+        "kernel" => Some(or_unknown(&info.kernel)),
+        "hostname" | "host" => Some(or_unknown(&info.host_name)),
+        "wm" => Some(or_unknown(&info.desktop)),
+        "packages" => Some(format_packages(&info.packages)),
+        "shell" => Some(or_unknown(&info.shell)),
+        "cpu" => Some(info.cpu.as_ref().map(format_cpu).unwrap_or("Unknown".to_string())),
         "gpu" => {
-            if info.gpu.is_empty() { Some("Unknown".to_string()) }
+            if info.gpu.is_empty() { Some("Unknown GPU".to_string()) }
             else { Some(info.gpu.join(" / ")) }
         },
-        "memory" => Some(info.memory.clone()),
-        "swap" => Some(info.swap.clone()),
+        "memory" => Some(info.memory.as_ref().map(format_memory).unwrap_or("Unknown".to_string())),
+        "swap" => Some(info.swap.as_ref().map(format_memory).unwrap_or("Unknown".to_string())),
         "disk" => {
              if info.disks.is_empty() { Some("Unknown".to_string()) }
-             else { Some(info.disks[0].clone()) } // Simplified
+             else { Some(format_disk(&info.disks[0])) } // Simplified
         },
-        "battery" => Some(info.battery.clone()),
-        "uptime" => Some(info.uptime.clone()),
-        "terminal" => Some(info.terminal.clone()),
-        "user" => Some(info.user.clone()),
-        "datetime" => Some(info.datetime.clone()),
-        "local_ip" => Some(info.local_ip.clone()),
+        "battery" => Some(format_battery(&info.battery)),
+        "uptime" => Some(info.uptime_secs.map(format_uptime).unwrap_or("Unknown".to_string())),
+        "terminal" => Some(or_unknown(&info.terminal)),
+        "user" => Some(or_unknown(&info.user)),
+        "datetime" => Some(or_unknown(&info.datetime)),
+        "local_ip" => Some(info.local_ip.map(|ip| ip.to_string()).unwrap_or("127.0.0.1".to_string())),
         "palette" => None, // Handled in prepare_render_tree
-        "header" => Some(format!("{}@{}", info.user, info.host_name)), // Custom module for header
+        "header" => Some(format!("{}@{}", or_unknown(&info.user), or_unknown(&info.host_name))), // Custom module for header
         "sep" => Some("---".to_string()),
         _ => None,
     }
 }
 
+// --- Value formatting ---
+
+fn or_unknown(value: &Option<String>) -> String {
+    value.clone().unwrap_or("Unknown".to_string())
+}
+
+fn format_os(os: &OsInfo) -> String {
+    format!(
+        "{} {} {}",
+        os.name.as_deref().unwrap_or("Unknown"),
+        os.version.as_deref().unwrap_or(""),
+        os.arch
+    )
+}
+
+/// Value of an `os.<field>` sub-module, or `None` if `field` is not a known os-release field.
+fn format_os_field(os: Option<&OsInfo>, field: &str) -> Option<String> {
+    let get: fn(&OsInfo) -> Option<String> = match field {
+        "name" => |os| os.name.clone(),
+        "version" => |os| os.version.clone(),
+        "arch" => |os| Some(os.arch.clone()),
+        "pretty_name" => |os| os.pretty_name.clone(),
+        "id" => |os| os.id.clone(),
+        "id_like" => |os| (!os.id_like.is_empty()).then(|| os.id_like.join(" ")),
+        "codename" | "version_codename" => |os| os.version_codename.clone(),
+        "build_id" => |os| os.build_id.clone(),
+        "variant" => |os| os.variant.clone(),
+        _ => return None,
+    };
+    Some(os.and_then(get).unwrap_or("Unknown".to_string()))
+}
+
+fn format_cpu(cpu: &CpuInfo) -> String {
+    format!("{} ({}) @ {:.2} GHz", cpu.brand, cpu.threads, cpu.freq_mhz as f64 / 1000.0)
+}
+
+fn format_battery(battery: &Option<BatteryInfo>) -> String {
+    match battery {
+        Some(b) => format!("{}% [{}]", b.percent, b.state),
+        None => "100% [AC Connected]".to_string(),
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    let hours = secs / 3600;
+    let mins = (secs % 3600) / 60;
+    format!("{} hours, {} mins", hours, mins)
+}
+
+fn format_packages(packages: &[PackageCount]) -> String {
+    if packages.is_empty() {
+        return "Unknown".to_string();
+    }
+    packages
+        .iter()
+        .map(|p| match &p.source {
+            Some(source) => format!("{} ({}-{})", p.count, p.manager, source),
+            None => format!("{} ({})", p.count, p.manager),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn to_gib(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0 / 1024.0
+}
+
+fn format_memory(mem: &MemoryInfo) -> String {
+    if mem.total_bytes == 0 {
+        return "0 B / 0 B (0%)".to_string();
+    }
+    format!("{:.2} GiB / {:.2} GiB ({:.0}%)", to_gib(mem.used_bytes), to_gib(mem.total_bytes), mem.percent)
+}
+
+fn format_disk(disk: &DiskInfo) -> String {
+    format!(
+        "{:.2} GiB / {:.2} GiB ({:.0}%) - {}",
+        to_gib(disk.used_bytes),
+        to_gib(disk.total_bytes),
+        disk.percent,
+        disk.fs
+    )
+}
+
 // --- Renderers ---
 
-fn render_classic(nodes: &[RenderNode], config: &Config) -> Vec<String> {
+const BORDER: Color = Color::Indexed(2);
+const MUTED: Color = Color::Indexed(240);
+
+fn render_classic(nodes: &[RenderNode], config: &Config, p: &Painter) -> Vec<String> {
     let mut lines = Vec::new();
     // Flatten
     for node in nodes {
         match node {
             RenderNode::Line { key, value, icon } => {
-                lines.push(format_line(key, value, icon, config));
+                lines.push(format_line(key, value, icon, config, p));
             },
             RenderNode::Group { title, children } => {
                 lines.push(format!("-- {} --", title));
                 for child in children {
                      if let RenderNode::Line { key, value, icon } = child {
-                         lines.push(format_line(key, value, icon, config));
+                         lines.push(format_line(key, value, icon, config, p));
                      }
                 }
             },
@@ -211,87 +659,106 @@ fn render_classic(nodes: &[RenderNode], config: &Config) -> Vec<String> {
     lines
 }
 
-fn render_classic_variants(nodes: &[RenderNode], config: &Config, variant: &str) -> Vec<String> {
+fn render_classic_variants(nodes: &[RenderNode], config: &Config, variant: &str, width: Option<usize>, p: &Painter) -> Vec<String> {
     let mut lines = Vec::new();
     let flat_items = flatten_nodes(nodes);
+    let nf = nerd_font_width(config);
     
     match variant {
         "box" => {
-             let max_len = flat_items.iter().map(|(k, v, i)| {
-                let content = format_line_content(k, v, i, config);
-                strip_ansi_codes(&content).chars().count()
-            }).max().unwrap_or(0);
+            // "│ " + content + " │"
+            let inner = width.map(|w| w.saturating_sub(4));
+            let mut rows = Vec::new();
+            for (key, val, icon) in &flat_items {
+                let label = p.paint(module_style(key, config), format!("{} ", icon));
+                let label_width = display_width(&label, nf);
+                let wrapped = wrap(val, inner.map(|w| w.saturating_sub(label_width)), nf);
+                for (idx, part) in wrapped.into_iter().enumerate() {
+                    let prefix = if idx == 0 { label.clone() } else { " ".repeat(label_width) };
+                    rows.push(format!("{}{}", prefix, part));
+                }
+            }
+            let max_len = rows.iter().map(|r| display_width(r, nf)).max().unwrap_or(0);
             
             let border_len = max_len + 2; // +2 for padding space
             lines.push(format!("╭{}╮", "─".repeat(border_len)));
             
-            for (key, val, icon) in flat_items {
-                let content = format_line_content(&key, &val, &icon, config);
-                let visual_len = strip_ansi_codes(&content).chars().count();
-                let padding = max_len - visual_len;
+            for content in rows {
+                let padding = max_len - display_width(&content, nf);
                 lines.push(format!("│ {} {}│", content, " ".repeat(padding)));
             }
             lines.push(format!("╰{}╯", "─".repeat(border_len)));
         },
         "pacman" => {
              // Header
-            let icons = config.header_icons.as_ref().map(|v| v.clone()).unwrap_or_default();
-            let mut header = String::from("\x1b[32m╭─ \x1b[0m");
+            let icons = config.header_icons.clone().unwrap_or_default();
+            let mut header = p.color(Color::Named(2), "╭─ ");
             for (idx, icon) in icons.iter().enumerate() {
                 let color = match idx % 5 {
-                     0 => "33", // Yellow
-                     1 => "31", // Red
-                     2 => "35", // Magenta
-                     3 => "36", // Cyan
-                     4 => "33", // Orange-ish
-                     _ => "37",
+                     0 => Color::Named(3), // Yellow
+                     1 => Color::Named(1), // Red
+                     2 => Color::Named(5), // Magenta
+                     3 => Color::Named(6), // Cyan
+                     _ => Color::Named(3), // Orange-ish
                 };
-                header.push_str(&format!("\x1b[{}m{} \x1b[0m", color, icon));
+                header.push_str(&p.color(color, format!("{} ", icon)));
             }
-            header.push_str("\x1b[32m────────────────╮\x1b[0m");
+            header.push_str(&p.color(Color::Named(2), "────────────────╮"));
             lines.push(header);
             
             // Content
             for (key, val, icon) in flat_items {
-                 lines.push(format_line(&key, &val, &icon, config));
+                 lines.push(format_line(&key, &val, &icon, config, p));
             }
             
             // Footer
             let footer_text = config.footer_text.as_deref().unwrap_or("X");
              lines.push(format!(
-                "\x1b[32m╰────────── \x1b[37m{}\x1b[32m ──────────╯\x1b[0m", 
-                footer_text
+                "{}{}{}",
+                p.color(Color::Named(2), "╰────────── "),
+                p.color(Color::Named(7), footer_text),
+                p.color(Color::Named(2), " ──────────╯")
             ));
         },
         "line" | "dots" => {
             for (idx, (key, val, icon)) in flat_items.iter().enumerate() {
-                lines.push(format_line(key, val, icon, config));
+                lines.push(format_line(key, val, icon, config, p));
                 if (idx + 1) % 3 == 0 && idx != flat_items.len() - 1 {
                      let sep = if variant == "line" { "──────────────────────────────" } else { ".............................." };
-                     lines.push(format!("\x1b[90m{}\x1b[0m", sep));
+                     lines.push(p.color(Color::Named(8), sep));
                 }
             }
         },
         "bottom_line" => {
              for (key, val, icon) in flat_items {
-                lines.push(format_line(&key, &val, &icon, config));
+                lines.push(format_line(&key, &val, &icon, config, p));
             }
-            lines.push("\x1b[37m──────────────────────────────\x1b[0m".to_string());
+            lines.push(p.color(Color::Named(7), "──────────────────────────────"));
         },
-        _ => return render_classic(nodes, config),
+        _ => return render_classic(nodes, config, p),
     }
     lines
 }
 
 // Image 1: Side Block
-fn render_side_block(nodes: &[RenderNode], config: &Config) -> Vec<String> {
+fn render_side_block(nodes: &[RenderNode], config: &Config, width: Option<usize>, p: &Painter) -> Vec<String> {
     let mut lines = Vec::new();
     let flat_items = flatten_nodes(nodes);
+    let nf = nerd_font_width(config);
     
     // Calculate max key length (use icon as label)
-    let max_key_len = flat_items.iter().map(|(_, _, icon)| strip_ansi_codes(icon).chars().count()).max().unwrap_or(0);
+    let max_key_len = flat_items.iter().map(|(_, _, icon)| display_width(icon, nf)).max().unwrap_or(0);
+    // Both boxes, their padding and the space between them take 9 columns
+    let val_budget = width.map(|w| w.saturating_sub(max_key_len + 9));
+    let rows: Vec<(String, Vec<String>, String)> = flat_items
+        .into_iter()
+        .map(|(key, val, icon)| {
+            let wrapped = wrap(&val, val_budget, nf);
+            (key, wrapped, icon)
+        })
+        .collect();
     // Calculate max val length
-    let max_val_len = flat_items.iter().map(|(_, v, _)| strip_ansi_codes(v).chars().count()).max().unwrap_or(0);
+    let max_val_len = rows.iter().flat_map(|(_, parts, _)| parts.iter().map(|v| display_width(v, nf))).max().unwrap_or(0);
 
     let left_width = max_key_len + 2;
     let right_width = max_val_len + 2;
@@ -299,35 +766,38 @@ fn render_side_block(nodes: &[RenderNode], config: &Config) -> Vec<String> {
     // Top borders
     // ╭───╮ ╭───╮
     let top = format!(
-        "\x1b[38;5;2m╭{}╮\x1b[0m \x1b[38;5;2m╭{}╮\x1b[0m", 
-        "─".repeat(left_width), 
-        "─".repeat(right_width)
+        "{} {}",
+        p.color(BORDER, format!("╭{}╮", "─".repeat(left_width))),
+        p.color(BORDER, format!("╭{}╮", "─".repeat(right_width)))
     );
     lines.push(top);
 
-    for (key, val, icon) in flat_items {
+    let bar = p.color(BORDER, "│");
+    for (key, parts, icon) in rows {
         // Color key based on config or rainbow
-        let color_code = get_color_code(&key, config);
         // Use icon as label text!
-        let key_str = format!("\x1b[{}m{:<width$}\x1b[0m", color_code, icon, width = max_key_len);
+        let key_padding = " ".repeat(max_key_len - display_width(&icon, nf));
+        let key_str = p.paint(module_style(&key, config), format!("{}{}", icon, key_padding));
         
-        let val_stripped_len = strip_ansi_codes(&val).chars().count();
-        let padding = max_val_len - val_stripped_len;
+        for (idx, val) in parts.iter().enumerate() {
+            let label = if idx == 0 { key_str.clone() } else { " ".repeat(max_key_len) };
+            let padding = max_val_len - display_width(val, nf);
 
-        let line = format!(
-            "\x1b[38;5;2m│\x1b[0m {} \x1b[38;5;2m│\x1b[0m \x1b[38;5;2m│\x1b[0m {}{} \x1b[38;5;2m│\x1b[0m",
-            key_str,
-            val,
-            " ".repeat(padding)
-        );
-        lines.push(line);
+            let line = format!(
+                "{bar} {} {bar} {bar} {}{} {bar}",
+                label,
+                val,
+                " ".repeat(padding)
+            );
+            lines.push(line);
+        }
     }
 
     // Bottom borders
     let bottom = format!(
-        "\x1b[38;5;2m╰{}╯\x1b[0m \x1b[38;5;2m╰{}╯\x1b[0m", 
-        "─".repeat(left_width), 
-        "─".repeat(right_width)
+        "{} {}",
+        p.color(BORDER, format!("╰{}╯", "─".repeat(left_width))),
+        p.color(BORDER, format!("╰{}╯", "─".repeat(right_width)))
     );
     lines.push(bottom);
 
@@ -335,7 +805,7 @@ fn render_side_block(nodes: &[RenderNode], config: &Config) -> Vec<String> {
 }
 
 // Image 2: Tree
-fn render_tree(nodes: &[RenderNode], config: &Config) -> Vec<String> {
+fn render_tree(nodes: &[RenderNode], config: &Config, p: &Painter) -> Vec<String> {
     let mut lines = Vec::new();
     
     for node in nodes {
@@ -343,10 +813,10 @@ fn render_tree(nodes: &[RenderNode], config: &Config) -> Vec<String> {
             RenderNode::Group { title, children } => {
                 // Root: [Icon] Title
                 // Find icon for title if exists, or use default
-                let icon = config.icons.get(title.to_lowercase().as_str()).map(|s| s.as_str()).unwrap_or(""); // Default PC icon
-                let color_code = get_color_code(&title.to_lowercase(), config);
+                let icon = config.icons.get(title.to_lowercase().as_str()).map(|s| s.as_str()).unwrap_or(""); // Default PC icon
+                let style = module_style(&title.to_lowercase(), config);
                 
-                lines.push(format!("\x1b[{}m{} {}\x1b[0m", color_code, icon, title));
+                lines.push(p.paint(style, format!("{} {}", icon, title)));
                 
                 for (idx, child) in children.iter().enumerate() {
                     let is_last = idx == children.len() - 1;
@@ -354,12 +824,10 @@ fn render_tree(nodes: &[RenderNode], config: &Config) -> Vec<String> {
                     
                     if let RenderNode::Line { key, value, icon: _ } = child {
                         // Tree style: ├── Key Value
-                         let key_color = get_color_code(key, config);
                          lines.push(format!(
-                             "\x1b[38;5;240m{}\x1b[0m \x1b[{}m{}\x1b[0m {}", 
-                             prefix, 
-                             key_color, 
-                             key, 
+                             "{} {} {}",
+                             p.color(MUTED, prefix),
+                             p.paint(module_style(key, config), key),
                              value
                          ));
                     }
@@ -367,7 +835,7 @@ fn render_tree(nodes: &[RenderNode], config: &Config) -> Vec<String> {
             },
             RenderNode::Line { key, value, icon } => {
                 // Top level item
-                 lines.push(format_line(key, value, icon, config));
+                 lines.push(format_line(key, value, icon, config, p));
             },
         }
     }
@@ -375,7 +843,7 @@ fn render_tree(nodes: &[RenderNode], config: &Config) -> Vec<String> {
 }
 
 // Image 3: Section
-fn render_section(nodes: &[RenderNode], config: &Config) -> Vec<String> {
+fn render_section(nodes: &[RenderNode], config: &Config, p: &Painter) -> Vec<String> {
     let mut lines = Vec::new();
     
     for node in nodes {
@@ -383,8 +851,9 @@ fn render_section(nodes: &[RenderNode], config: &Config) -> Vec<String> {
             RenderNode::Group { title, children } => {
                 // ┌─── Title ───┐ (Simplified)
                 // ---- Title ----
-                let header = format!("\x1b[38;5;240m──────\x1b[0m \x1b[1m{}\x1b[0m \x1b[38;5;240m──────\x1b[0m", title);
-                lines.push(header);
+                let rule = p.color(MUTED, "──────");
+                let bold = Style { bold: true, ..Style::default() };
+                lines.push(format!("{} {} {}", rule, p.paint(bold, title), rule));
                 
                 for child in children {
                      if let RenderNode::Line { key, value, icon } = child {
@@ -394,12 +863,10 @@ fn render_section(nodes: &[RenderNode], config: &Config) -> Vec<String> {
                          // Let's use tree style
                          let _icon_display = if icon == "●" { "└" } else { icon }; // Use icon if specific, else tree
                          
-                         let key_color = get_color_code(key, config);
                          lines.push(format!(
-                             "\x1b[38;5;240m│\x1b[0m \x1b[{}m{} {}:\x1b[0m {}", 
-                             key_color,
-                             icon,
-                             key, 
+                             "{} {} {}",
+                             p.color(MUTED, "│"),
+                             p.paint(module_style(key, config), format!("{} {}:", icon, key)),
                              value
                          ));
                     }
@@ -407,7 +874,7 @@ fn render_section(nodes: &[RenderNode], config: &Config) -> Vec<String> {
                 lines.push("".to_string()); // Empty line
             },
              RenderNode::Line { key, value, icon } => {
-                 lines.push(format_line(key, value, icon, config));
+                 lines.push(format_line(key, value, icon, config, p));
             },
         }
     }
@@ -429,74 +896,135 @@ fn flatten_nodes(nodes: &[RenderNode]) -> Vec<(String, String, String)> {
     items
 }
 
-fn format_line(key: &str, value: &str, icon: &str, config: &Config) -> String {
-    let color_code = get_color_code(key, config);
-    format!(
-        "\x1b[{}m{} \x1b[0m{}", 
-        color_code, 
-        icon, 
-        value
-    )
+fn format_line(key: &str, value: &str, icon: &str, config: &Config, p: &Painter) -> String {
+    format!("{}{}", p.paint(module_style(key, config), format!("{} ", icon)), value)
 }
 
-fn format_line_content(key: &str, value: &str, icon: &str, config: &Config) -> String {
-    let color_code = get_color_code(key, config);
-    format!("\x1b[{}m{} \x1b[0m{}", color_code, icon, value)
+/// Cells taken by a Nerd Font icon with the configured font and terminal.
+fn nerd_font_width(config: &Config) -> usize {
+    config.nerd_font_width.unwrap_or(DEFAULT_NERD_FONT_WIDTH).clamp(1, 2)
 }
 
-fn get_color_code(key: &str, config: &Config) -> &'static str {
-    let color_name = config.colors.get(key).map(|s| s.as_str()).unwrap_or("White");
-    match color_name.to_lowercase().as_str() {
-        "black" => "30",
-        "red" => "31",
-        "green" => "32",
-        "yellow" => "33",
-        "blue" => "34",
-        "magenta" => "35",
-        "cyan" => "36",
-        "white" => "37",
-        "grey" | "gray" => "90",
-        _ => "37",
+/// Looks up a per-module setting; sub-modules such as `os.id` fall back to their parent's.
+fn module_setting<'a>(settings: &'a HashMap<String, String>, key: &str) -> Option<&'a String> {
+    settings
+        .get(key)
+        .or_else(|| key.split_once('.').and_then(|(parent, _)| settings.get(parent)))
+}
+
+/// The module's configured style (white when unset or without a color).
+fn module_style(key: &str, config: &Config) -> Style {
+    let mut style = module_setting(&config.colors, key).map(|s| Style::parse(s)).unwrap_or_default();
+    if style.fg.is_none() {
+        style.fg = Some(Color::Named(7));
     }
+    style
 }
 
-fn format_palette(config: &Config) -> String {
+fn format_palette(config: &Config, p: &Painter) -> String {
     let style = config.palette_style.as_deref().unwrap_or("squares");
     let mut s = String::new();
     
-    // ANSI color codes (0-7 correspond to 30-37 fg, 40-47 bg)
-    let colors = [0, 1, 2, 3, 4, 5, 6, 7];
+    // The eight normal terminal colors
+    let colors = (0..8).map(Color::Named);
     
     match style {
-        "squares" => {
-            for c in colors {
-                s.push_str(&format!("\x1b[{}m  \x1b[0m ", c + 40));
-            }
-        },
         "circles" => {
             for c in colors {
-                s.push_str(&format!("\x1b[{}m●\x1b[0m ", c + 30));
+                s.push_str(&format!("{} ", p.color(c, "●")));
             }
         },
         "triangles" => {
             for c in colors {
-                s.push_str(&format!("\x1b[{}m▲\x1b[0m ", c + 30));
+                s.push_str(&format!("{} ", p.color(c, "▲")));
             }
         },
         "lines" => {
             for c in colors {
-                s.push_str(&format!("\x1b[{}m███\x1b[0m", c + 30));
+                s.push_str(&p.color(c, "███"));
             }
         },
+        // "squares" and anything unknown
         _ => {
              for c in colors {
-                s.push_str(&format!("\x1b[{}m  \x1b[0m ", c + 40));
+                s.push_str(&format!("{} ", p.paint(Style::bg(c), "  ")));
             }
         }
     }
     s
 }
 
+/// Replaces neofetch-style `${c1}`..`${c9}` placeholders with the matching
+/// `logo_colors` entry. A color stays active across lines until the next
+/// placeholder, so every line is re-opened with it and reset at its end.
+fn apply_logo_colors(lines: Vec<String>, colors: &[String], p: &Painter) -> Vec<String> {
+    if !lines.iter().any(|l| l.contains("${c")) {
+        return lines;
+    }
+    let mut active = String::new();
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut out = active.clone();
+            let mut rest = line.as_str();
+            while let Some(pos) = rest.find("${c") {
+                out.push_str(&rest[..pos]);
+                let after = &rest[pos + 3..];
+                let digit = after.chars().next().and_then(|c| c.to_digit(10)).filter(|d| *d >= 1);
+                if let Some(d) = digit
+                    && after[1..].starts_with('}')
+                {
+                    // Placeholders without a configured color fall back to the terminal default.
+                    active = match colors.get(d as usize - 1) {
+                        Some(name) => p.start(Style::fg(Color::parse(name).unwrap_or(Color::Named(7)))),
+                        None => p.reset().to_string(),
+                    };
+                    out.push_str(&active);
+                    rest = &after[2..];
+                } else {
+                    out.push_str("${c");
+                    rest = after;
+                }
+            }
+            out.push_str(rest);
+            out.push_str(p.reset());
+            out
+        })
+        .collect()
+}
+
+/// Repaints a logo per character according to `logo_style`, discarding any
+/// colors of its own. `stops` are the gradient stops or the per-line colors.
+fn paint_logo(lines: Vec<String>, style: &str, stops: &[Color], p: &Painter) -> Vec<String> {
+    let plain: Vec<Vec<char>> = apply_logo_colors(lines, &[], p)
+        .iter()
+        .map(|l| strip_ansi_codes(l).chars().collect())
+        .collect();
+    let width = plain.iter().map(|l| l.len()).max().unwrap_or(0);
+    let height = plain.len();
+    let fraction = |i: usize, n: usize| if n > 1 { i as f64 / (n - 1) as f64 } else { 0.0 };
+
+    plain
+        .iter()
+        .enumerate()
+        .map(|(row, chars)| {
+            p.paint_runs(chars.iter().enumerate().map(|(col, &ch)| {
+                if ch.is_whitespace() {
+                    return (ch, None);
+                }
+                let color = match style {
+                    "gradient" | "gradient-horizontal" => gradient(stops, fraction(col, width)),
+                    "gradient-vertical" => gradient(stops, fraction(row, height)),
+                    "lines" => stops.get(row % stops.len().max(1)).copied().unwrap_or(Color::Named(7)),
+                    "rainbow" => rainbow((col + row * 2) as f64 / (width + height * 2).max(1) as f64),
+                    _ => stops.first().copied().unwrap_or(Color::Named(7)),
+                };
+                (ch, Some(Style::fg(color)))
+            }))
+        })
+        .collect()
+}
+
 fn get_default_ascii() -> String {
     r#"
 __  __
@@ -522,3 +1050,44 @@ Z0123456789+/woXos/Linux/Rocks/==
 ----------END PUBLIC KEY-----------
 "#.trim().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorMode;
+
+    fn colored(lines: &[&str], colors: &[&str]) -> Vec<String> {
+        let lines = lines.iter().map(|l| l.to_string()).collect();
+        let colors: Vec<String> = colors.iter().map(|c| c.to_string()).collect();
+        apply_logo_colors(lines, &colors, &Painter::new(ColorMode::Always))
+    }
+
+    #[test]
+    fn replaces_every_placeholder_and_carries_color_across_lines() {
+        let names = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "bright-red"];
+        let line: String = (1..=9).map(|n| format!("${{c{}}}{}", n, n)).collect();
+        let mut expected: String = (1..=8).map(|n| format!("\x1b[{}m{}", 29 + n, n)).collect();
+        expected.push_str("\x1b[91m9\x1b[0m");
+        assert_eq!(colored(&[&line], &names), [expected]);
+
+        assert_eq!(colored(&["${c2}ab", "cd"], &names), ["\x1b[31mab\x1b[0m", "\x1b[31mcd\x1b[0m"]);
+    }
+
+    #[test]
+    fn leaves_malformed_placeholders_alone() {
+        for text in ["${cX}a", "${c1", "${c0}a", "${c12}a", "${c", "${ç}"] {
+            assert_eq!(colored(&["${c1}", text], &["red"])[1], format!("\x1b[31m{}\x1b[0m", text));
+        }
+    }
+
+    #[test]
+    fn resets_placeholders_without_a_configured_color() {
+        assert_eq!(colored(&["${c1}a${c3}b"], &["red"]), ["\x1b[31ma\x1b[0mb\x1b[0m"]);
+        assert_eq!(colored(&["${c4}é"], &[]), ["\x1b[0mé\x1b[0m"]);
+    }
+
+    #[test]
+    fn keeps_logos_without_placeholders_untouched() {
+        assert_eq!(colored(&["\x1b[35m/\\\x1b[0m"], &["red"]), ["\x1b[35m/\\\x1b[0m"]);
+    }
+}
@@ -0,0 +1,185 @@
+//! How many terminal cells text occupies. Every layout measures through here
+//! so that borders line up with wide glyphs, emoji and Nerd Font icons.
+
+use console::AnsiCodeIterator;
+use unicode_width::UnicodeWidthStr;
+
+/// Cells assumed for a Nerd Font icon when `nerd_font_width` is not configured.
+pub const DEFAULT_NERD_FONT_WIDTH: usize = 1;
+
+/// Nerd Font icons live in the Private Use Areas, whose width Unicode leaves to
+/// the font: most terminals draw them in one cell, some (and some fonts) in two.
+pub fn is_nerd_font_glyph(c: char) -> bool {
+    matches!(c, '\u{e000}'..='\u{f8ff}' | '\u{f0000}'..='\u{ffffd}' | '\u{100000}'..='\u{10fffd}')
+}
+
+/// Display width of `s`, ignoring escape codes, with Nerd Font icons counted as
+/// `nerd_font_width` cells.
+pub fn display_width(s: &str, nerd_font_width: usize) -> usize {
+    AnsiCodeIterator::new(s)
+        .filter(|(_, is_ansi)| !is_ansi)
+        .map(|(text, _)| text_width(text, nerd_font_width))
+        .sum()
+}
+
+fn text_width(text: &str, nerd_font_width: usize) -> usize {
+    // Measure runs rather than single chars so that emoji sequences (`🖥️` with
+    // its variation selector) and combining marks keep their proper width.
+    let mut width = 0;
+    let mut run_start = 0;
+    for (i, c) in text.char_indices() {
+        if is_nerd_font_glyph(c) {
+            width += text[run_start..i].width() + nerd_font_width;
+            run_start = i + c.len_utf8();
+        }
+    }
+    width + text[run_start..].width()
+}
+
+/// Cuts `s` to at most `width` cells, ending it with an ellipsis. Escape codes
+/// after the cut are kept so that styles are still reset.
+pub fn truncate(s: &str, width: usize, nerd_font_width: usize) -> String {
+    if display_width(s, nerd_font_width) <= width {
+        return s.to_string();
+    }
+    let budget = width.saturating_sub(1);
+    let mut out = String::new();
+    let mut used = 0;
+    let mut cut = false;
+    for (part, is_ansi) in AnsiCodeIterator::new(s) {
+        if is_ansi {
+            out.push_str(part);
+            continue;
+        }
+        if cut {
+            continue;
+        }
+        for c in part.chars() {
+            let w = text_width(c.encode_utf8(&mut [0; 4]), nerd_font_width);
+            if used + w > budget {
+                cut = true;
+                break;
+            }
+            out.push(c);
+            used += w;
+        }
+        if cut {
+            out.push('…');
+        }
+    }
+    out
+}
+
+/// Breaks `value` into lines of at most `width` cells, preferring spaces.
+/// Styled values (the palette) cannot be split safely and are truncated instead.
+pub fn wrap(value: &str, width: Option<usize>, nerd_font_width: usize) -> Vec<String> {
+    let measure = |s: &str| display_width(s, nerd_font_width);
+    let Some(width) = width.filter(|w| *w > 0 && measure(value) > *w) else {
+        return vec![value.to_string()];
+    };
+    if value.contains('\x1b') {
+        return vec![truncate(value, width, nerd_font_width)];
+    }
+    // Leading spaces stay on the first line, as far as they fit; a value of
+    // nothing but spaces still yields a (blank) line so its row is drawn.
+    let indent = value.len() - value.trim_start_matches(' ').len();
+    let mut lines = Vec::new();
+    let mut current = " ".repeat(indent.min(width));
+    let mut indent_only = true;
+    for word in value[indent..].split(' ') {
+        let sep = usize::from(!indent_only && !current.is_empty());
+        if measure(&current) + sep + measure(word) <= width {
+            if sep == 1 {
+                current.push(' ');
+            }
+            current.push_str(word);
+            indent_only = false;
+            continue;
+        }
+        if indent_only {
+            current.clear();
+        } else if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        indent_only = false;
+        // Words longer than a whole line are split wherever the width runs out.
+        for ch in word.chars() {
+            if !current.is_empty() && measure(&current) + measure(ch.encode_utf8(&mut [0; 4])) > width {
+                lines.push(std::mem::take(&mut current));
+            }
+            current.push(ch);
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// Icons as they appear in configs, with their width at Nerd Font widths 1 and 2.
+    const CORPUS: &[(&str, usize, usize)] = &[
+        ("OS", 2, 2),
+        ("●", 1, 1),
+        ("\u{f31a}", 1, 2),         // nf-linux-tux
+        ("\u{f0ee0}", 1, 2),        // nf-md, in the supplementary PUA
+        ("\u{e795}", 1, 2),         // nf-dev-terminal
+        ("\u{f2db} CPU", 5, 6),     // icon followed by a label
+        ("🎨", 2, 2),               // the palette default
+        ("🖥️", 2, 2),               // emoji with variation selector 16
+        ("❤", 1, 1),                // text presentation
+        ("漢字", 4, 4),
+        ("ﾃｽﾄ", 3, 3),              // half-width katakana
+        ("e\u{301}", 1, 1),         // combining acute accent
+        ("\x1b[1;36m\u{f31a}\x1b[0m", 1, 2),
+        ("", 0, 0),
+    ];
+
+    #[test]
+    fn measures_icon_corpus() {
+        for (icon, one, two) in CORPUS {
+            assert_eq!(display_width(icon, 1), *one, "{:?} at width 1", icon);
+            assert_eq!(display_width(icon, 2), *two, "{:?} at width 2", icon);
+        }
+    }
+
+    #[test]
+    fn nerd_font_width_only_affects_private_use_glyphs() {
+        let configs = Path::new(env!("CARGO_MANIFEST_DIR")).join("configs");
+        let mut icons = 0;
+        for entry in fs::read_dir(configs).unwrap() {
+            let content = fs::read_to_string(entry.unwrap().path()).unwrap();
+            for c in content.chars().filter(|c| !c.is_ascii()) {
+                let icon = c.to_string();
+                let expected = if is_nerd_font_glyph(c) { 1 } else { 0 };
+                assert_eq!(display_width(&icon, 2) - display_width(&icon, 1), expected, "{:?}", icon);
+                icons += 1;
+            }
+        }
+        assert!(icons > 0);
+    }
+
+    #[test]
+    fn truncates_by_cells_and_keeps_resets() {
+        assert_eq!(truncate("漢字漢字", 5, 1), "漢字…");
+        assert_eq!(truncate("\x1b[31mabcdef\x1b[0m", 4, 1), "\x1b[31mabc…\x1b[0m");
+        assert_eq!(truncate("\u{f31a} tux", 4, 2), "\u{f31a} …");
+        assert_eq!(truncate("short", 10, 1), "short");
+    }
+
+    #[test]
+    fn wraps_on_spaces_and_splits_long_words() {
+        assert_eq!(wrap("one two three", Some(7), 1), ["one two", "three"]);
+        assert_eq!(wrap("abcdefgh", Some(3), 1), ["abc", "def", "gh"]);
+        assert_eq!(wrap("漢字漢字", Some(5), 1), ["漢字", "漢字"]);
+        assert_eq!(wrap("fits", None, 1), ["fits"]);
+        assert_eq!(wrap("  indented text", Some(10), 1), ["  indented", "text"]);
+        assert_eq!(wrap(&" ".repeat(24), Some(10), 1), [" ".repeat(10)]);
+        assert_eq!(wrap("ab ", Some(2), 1), ["ab"]);
+    }
+}
@@ -0,0 +1,94 @@
+#!/usr/bin/env python3
+"""Regenerates the synthetic rpm databases used by the tests in src/rpmdb.rs.
+
+Each database holds a known number of installed packages:
+  sqlite/rpmdb.sqlite  120 rows in the Packages table (spread over interior pages)
+  ndb/Packages.db        7 used slots over two slot pages
+  bdb/Packages           5 package keys plus rpm's bookkeeping record 0
+"""
+import os
+import random
+import sqlite3
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def write_sqlite():
+    path = os.path.join(HERE, "sqlite", "rpmdb.sqlite")
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    if os.path.exists(path):
+        os.remove(path)
+    db = sqlite3.connect(path)
+    db.execute("PRAGMA page_size = 1024")
+    db.execute("PRAGMA journal_mode = DELETE")
+    db.execute("CREATE TABLE 'Packages' (hnum INTEGER PRIMARY KEY AUTOINCREMENT, blob BLOB NOT NULL)")
+    db.execute("CREATE TABLE 'Name' (key 'TEXT' NOT NULL, hnum INTEGER NOT NULL)")
+    db.execute("CREATE INDEX 'Name_key_idx' ON 'Name'(key ASC)")
+    rng = random.Random(42)
+    for i in range(130):
+        # A few headers are larger than a page so overflow pages are exercised too.
+        size = 3000 if i % 25 == 0 else rng.randint(16, 96)
+        cur = db.execute("INSERT INTO Packages (blob) VALUES (?)", (bytes(size),))
+        db.execute("INSERT INTO Name VALUES (?, ?)", ("pkg%d" % i, cur.lastrowid))
+    db.execute("DELETE FROM Packages WHERE hnum % 13 = 0")
+    db.commit()
+    db.close()
+
+
+def write_ndb():
+    path = os.path.join(HERE, "ndb", "Packages.db")
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    page_size, header_size, slot_size, slot_pages = 4096, 32, 16, 2
+    data = bytearray(page_size * slot_pages)
+    struct.pack_into("<4sIIII", data, 0, b"RpmP", 0, 1, slot_pages, 8)
+    used = {3, 10, 50, 200, 253, 260, 400}
+    slot = 0
+    for offset in range(header_size, len(data), slot_size):
+        pkgidx = len([u for u in used if u <= slot]) if slot in used else 0
+        blkoff = slot_pages * page_size // 16 + slot if pkgidx else 0
+        struct.pack_into("<4sIII", data, offset, b"Slot", pkgidx, blkoff, 4 if pkgidx else 0)
+        slot += 1
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+def write_bdb():
+    path = os.path.join(HERE, "bdb", "Packages")
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    page_size = 512
+    P_OVERFLOW, P_HASHMETA, P_HASH = 7, 8, 13
+    H_KEYDATA, H_OFFPAGE = 1, 3
+
+    meta = bytearray(page_size)
+    struct.pack_into("<IIIII", meta, 8, 0, 0x061561, 9, page_size, 0)
+    meta[25] = P_HASHMETA
+    struct.pack_into("<I", meta, 32, 3)
+
+    def hash_page(pgno, keys):
+        page = bytearray(page_size)
+        struct.pack_into("<III", page, 8, pgno, 0, 0)
+        struct.pack_into("<H", page, 20, len(keys) * 2)
+        page[25] = P_HASH
+        end = page_size
+        for i, key in enumerate(keys):
+            key_item = bytes([H_KEYDATA]) + struct.pack("<I", key)
+            data_item = bytes([H_OFFPAGE, 0, 0, 0]) + struct.pack("<II", 3, 4000)
+            end -= len(key_item)
+            page[end:end + len(key_item)] = key_item
+            struct.pack_into("<H", page, 26 + i * 4, end)
+            end -= len(data_item)
+            page[end:end + len(data_item)] = data_item
+            struct.pack_into("<H", page, 26 + i * 4 + 2, end)
+        return page
+
+    overflow = bytearray(page_size)
+    overflow[25] = P_OVERFLOW
+    with open(path, "wb") as f:
+        f.write(meta + hash_page(1, [0, 1, 2]) + hash_page(2, [3, 4, 5]) + overflow)
+
+
+if __name__ == "__main__":
+    write_sqlite()
+    write_ndb()
+    write_bdb()
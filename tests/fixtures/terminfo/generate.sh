@@ -0,0 +1,24 @@
+#!/bin/sh
+# Regenerates the compiled terminfo entries used by the tests in src/terminfo.rs
+# from the system's ncurses database (needs infocmp and tic from ncurses 6.1+).
+#
+#   l/linux           16-bit numbers, colors#8, extended AX, U8#1 and E3
+#   l/linux-tc        the same plus the extended Tc boolean
+#   x/xterm-256color  32-bit numbers (pairs#0x10000), colors#0x100
+#   x/xterm-tc        the same plus the extended Tc boolean
+#   x/xterm-direct    colors#0x1000000 plus the extended RGB boolean
+set -e
+cd "$(dirname "$0")"
+src=$(mktemp)
+trap 'rm -f "$src"' EXIT
+
+compile() {
+    tic -x -o . "$src"
+}
+
+infocmp -x linux >"$src" && compile
+infocmp -x linux | sed 's/^linux|[^,]*,/linux-tc|Linux console with Tc,\n\tTc,/' >"$src" && compile
+infocmp -x xterm-256color >"$src" && compile
+infocmp -x xterm-256color | sed 's/^xterm-256color|[^,]*,/xterm-tc|xterm with Tc,\n\tTc,/' >"$src" && compile
+infocmp -x xterm-256color \
+    | sed -e 's/^xterm-256color|[^,]*,/xterm-direct|xterm with direct color,\n\tRGB,/' -e 's/colors#0x100,/colors#0x1000000,/' >"$src" && compile